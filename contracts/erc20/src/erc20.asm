@@ -3,6 +3,7 @@
 ;=============================================================================
 ;
 ; Calldata layout: 8-byte little-endian selector followed by u64 arguments.
+; It is copied to memory at offset 0 on entry.
 ;
 ; Selectors:
 ;   0x00 totalSupply()
@@ -31,7 +32,10 @@
 .entry main
 
 main:
+    ; Copy calldata to memory at 0 so handlers can read arguments with LOAD64.
     LOADI R15, 0
+    CALLDATASIZE R14
+    CALLDATACOPY R15, R15, R14
     LOAD64 R0, R15
 
     LOADI R1, 0
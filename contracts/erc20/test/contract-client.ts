@@ -1,4 +1,4 @@
-import { produceBlock, runMinichain } from "@minichain/contract-test-harness";
+import { gasProfileArgs, produceBlock, runMinichain } from "@minichain/contract-test-harness";
 
 const ERC20_CONTRACT_PATH = (() => {
   const base = import.meta.dir.replace(/\/test$/, "");
@@ -84,6 +84,7 @@ export class ContractClient {
       "--to", this.address,
       "--data", encodeCall(selector, args),
       "--data-dir", this.dataDir,
+      ...gasProfileArgs(ERC20_CONTRACT_PATH, `selector-${selector}`),
     );
     return decodeU64(output);
   }
@@ -96,6 +97,7 @@ export class ContractClient {
       "--to", this.address,
       "--data", encodeCall(selector, []),
       "--data-dir", this.dataDir,
+      ...gasProfileArgs(ERC20_CONTRACT_PATH, `selector-${selector}`),
     );
     return decodeString(output);
   }
@@ -32,6 +32,11 @@ mod opcodes {
     pub const JUMPI: u8 = 0x03;
     pub const CALL: u8 = 0x04;
     pub const RET: u8 = 0x05;
+    pub const RETURN: u8 = 0x06;
+    pub const JSUB: u8 = 0x07;
+    pub const JRET: u8 = 0x08;
+    pub const JSUBI: u8 = 0x09;
+    pub const REVERTD: u8 = 0x0E;
     pub const REVERT: u8 = 0x0F;
 
     // Arithmetic (0x10-0x1F)
@@ -41,6 +46,12 @@ mod opcodes {
     pub const DIV: u8 = 0x13;
     pub const MOD: u8 = 0x14;
     pub const ADDI: u8 = 0x15;
+    pub const SDIV: u8 = 0x16;
+    pub const SMOD: u8 = 0x17;
+    pub const ADDC: u8 = 0x18;
+    pub const SUBC: u8 = 0x19;
+    pub const MULC: u8 = 0x1A;
+    pub const MULHI: u8 = 0x1B;
 
     // Bitwise (0x20-0x2F)
     pub const AND: u8 = 0x20;
@@ -49,6 +60,7 @@ mod opcodes {
     pub const NOT: u8 = 0x23;
     pub const SHL: u8 = 0x24;
     pub const SHR: u8 = 0x25;
+    pub const SAR: u8 = 0x26;
 
     // Comparison (0x30-0x3F)
     pub const EQ: u8 = 0x30;
@@ -58,6 +70,8 @@ mod opcodes {
     pub const LE: u8 = 0x34;
     pub const GE: u8 = 0x35;
     pub const ISZERO: u8 = 0x36;
+    pub const SLT: u8 = 0x37;
+    pub const SGT: u8 = 0x38;
 
     // Memory (0x40-0x4F)
     pub const LOAD8: u8 = 0x40;
@@ -66,10 +80,22 @@ mod opcodes {
     pub const STORE64: u8 = 0x43;
     pub const MSIZE: u8 = 0x44;
     pub const MCOPY: u8 = 0x45;
+    pub const CALLDATASIZE: u8 = 0x46;
+    pub const CALLDATALOAD: u8 = 0x47;
+    pub const CALLDATACOPY: u8 = 0x48;
 
     // Storage (0x50-0x5F)
     pub const SLOAD: u8 = 0x50;
     pub const SSTORE: u8 = 0x51;
+    pub const SLOADM: u8 = 0x52;
+    pub const SSTOREM: u8 = 0x53;
+    pub const TLOAD: u8 = 0x54;
+    pub const TSTORE: u8 = 0x55;
+
+    // Crypto (0x60-0x6F)
+    pub const HASH: u8 = 0x60;
+    pub const HASH64: u8 = 0x61;
+    pub const SIGVERIFY: u8 = 0x62;
 
     // Immediate (0x70-0x7F)
     pub const LOADI: u8 = 0x70;
@@ -82,6 +108,24 @@ mod opcodes {
     pub const BLOCKNUMBER: u8 = 0x83;
     pub const TIMESTAMP: u8 = 0x84;
     pub const GAS: u8 = 0x85;
+    pub const CALLERM: u8 = 0x86;
+    pub const ADDRESSM: u8 = 0x87;
+    pub const ADDREQ: u8 = 0x88;
+    pub const BALANCE: u8 = 0x89;
+    pub const SELFBALANCE: u8 = 0x8A;
+    pub const CHAINID: u8 = 0x8B;
+    pub const ORIGIN: u8 = 0x8C;
+    pub const GASPRICE: u8 = 0x8D;
+    pub const COINBASE: u8 = 0x8E;
+    pub const BLOCKHASH: u8 = 0x8F;
+
+    // System (0x90-0x9F)
+    pub const TRANSFER: u8 = 0x90;
+    pub const CREATE: u8 = 0x91;
+    pub const SYSCALL: u8 = 0x92;
+
+    // Events (0xA0-0xAF)
+    pub const LOG0: u8 = 0xA0;
 
     // Debug (0xF0-0xFF)
     pub const LOG: u8 = 0xF0;
@@ -115,6 +159,14 @@ impl Compiler {
         compiler.second_pass(program)
     }
 
+    /// Compile a program and return its label addresses alongside the bytecode
+    pub fn compile_with_labels(program: &Program) -> Result<(Vec<u8>, HashMap<String, u64>)> {
+        let mut compiler = Self::new();
+        compiler.first_pass(program)?;
+        let bytecode = compiler.second_pass(program)?;
+        Ok((bytecode, compiler.symbol_table))
+    }
+
     /// First pass: build symbol table
     fn first_pass(&mut self, program: &Program) -> Result<()> {
         let mut address: u64 = 0;
@@ -174,17 +226,26 @@ impl Compiler {
             Instruction::Halt => bytecode.push(opcodes::HALT),
             Instruction::Nop => bytecode.push(opcodes::NOP),
             Instruction::Ret => bytecode.push(opcodes::RET),
+            Instruction::JRet => bytecode.push(opcodes::JRET),
             Instruction::Revert => bytecode.push(opcodes::REVERT),
+            Instruction::RevertData { offset, len } => {
+                bytecode.push(opcodes::REVERTD);
+                bytecode.push((offset << 4) | len);
+            }
 
             // Single register
             Instruction::Jump { target } => {
                 bytecode.push(opcodes::JUMP);
                 bytecode.push(target << 4);
             }
-            Instruction::Call { target } => {
-                bytecode.push(opcodes::CALL);
+            Instruction::JSub { target } => {
+                bytecode.push(opcodes::JSUB);
                 bytecode.push(target << 4);
             }
+            Instruction::JSubLabel { label } => {
+                bytecode.push(opcodes::JSUBI);
+                bytecode.extend_from_slice(&self.resolve(label)?.to_le_bytes());
+            }
             Instruction::Log { src } => {
                 bytecode.push(opcodes::LOG);
                 bytecode.push(src << 4);
@@ -193,6 +254,10 @@ impl Compiler {
                 bytecode.push(opcodes::MSIZE);
                 bytecode.push(dst << 4);
             }
+            Instruction::CallDataSize { dst } => {
+                bytecode.push(opcodes::CALLDATASIZE);
+                bytecode.push(dst << 4);
+            }
             Instruction::Caller { dst } => {
                 bytecode.push(opcodes::CALLER);
                 bytecode.push(dst << 4);
@@ -217,12 +282,56 @@ impl Compiler {
                 bytecode.push(opcodes::GAS);
                 bytecode.push(dst << 4);
             }
+            Instruction::CallerM { off } => {
+                bytecode.push(opcodes::CALLERM);
+                bytecode.push(off << 4);
+            }
+            Instruction::AddressM { off } => {
+                bytecode.push(opcodes::ADDRESSM);
+                bytecode.push(off << 4);
+            }
+            Instruction::SelfBalance { dst } => {
+                bytecode.push(opcodes::SELFBALANCE);
+                bytecode.push(dst << 4);
+            }
+            Instruction::ChainId { dst } => {
+                bytecode.push(opcodes::CHAINID);
+                bytecode.push(dst << 4);
+            }
+            Instruction::Origin { off } => {
+                bytecode.push(opcodes::ORIGIN);
+                bytecode.push(off << 4);
+            }
+            Instruction::GasPrice { dst } => {
+                bytecode.push(opcodes::GASPRICE);
+                bytecode.push(dst << 4);
+            }
+            Instruction::Coinbase { off } => {
+                bytecode.push(opcodes::COINBASE);
+                bytecode.push(off << 4);
+            }
 
             // Two registers
             Instruction::JumpI { cond, target } => {
                 bytecode.push(opcodes::JUMPI);
                 bytecode.push((cond << 4) | target);
             }
+            Instruction::Balance { dst, addr } => {
+                bytecode.push(opcodes::BALANCE);
+                bytecode.push((dst << 4) | addr);
+            }
+            Instruction::BlockHash { off, number } => {
+                bytecode.push(opcodes::BLOCKHASH);
+                bytecode.push((off << 4) | number);
+            }
+            Instruction::Transfer { addr, amount } => {
+                bytecode.push(opcodes::TRANSFER);
+                bytecode.push((addr << 4) | amount);
+            }
+            Instruction::Return { offset, len } => {
+                bytecode.push(opcodes::RETURN);
+                bytecode.push((offset << 4) | len);
+            }
             Instruction::Mov { dst, src } => {
                 bytecode.push(opcodes::MOV);
                 bytecode.push((dst << 4) | src);
@@ -243,6 +352,10 @@ impl Compiler {
                 bytecode.push(opcodes::LOAD64);
                 bytecode.push((dst << 4) | addr);
             }
+            Instruction::CallDataLoad { dst, off } => {
+                bytecode.push(opcodes::CALLDATALOAD);
+                bytecode.push((dst << 4) | off);
+            }
             Instruction::Store8 { addr, src } => {
                 bytecode.push(opcodes::STORE8);
                 bytecode.push((addr << 4) | src);
@@ -259,6 +372,22 @@ impl Compiler {
                 bytecode.push(opcodes::SSTORE);
                 bytecode.push((key << 4) | value);
             }
+            Instruction::SLoadM { dst_off, key_off } => {
+                bytecode.push(opcodes::SLOADM);
+                bytecode.push((dst_off << 4) | key_off);
+            }
+            Instruction::SStoreM { key_off, val_off } => {
+                bytecode.push(opcodes::SSTOREM);
+                bytecode.push((key_off << 4) | val_off);
+            }
+            Instruction::TLoad { dst, key } => {
+                bytecode.push(opcodes::TLOAD);
+                bytecode.push((dst << 4) | key);
+            }
+            Instruction::TStore { key, value } => {
+                bytecode.push(opcodes::TSTORE);
+                bytecode.push((key << 4) | value);
+            }
 
             // Three registers
             Instruction::Add { dst, s1, s2 } => {
@@ -311,6 +440,51 @@ impl Compiler {
                 bytecode.push((dst << 4) | s1);
                 bytecode.push(s2 << 4);
             }
+            Instruction::SDiv { dst, s1, s2 } => {
+                bytecode.push(opcodes::SDIV);
+                bytecode.push((dst << 4) | s1);
+                bytecode.push(s2 << 4);
+            }
+            Instruction::SMod { dst, s1, s2 } => {
+                bytecode.push(opcodes::SMOD);
+                bytecode.push((dst << 4) | s1);
+                bytecode.push(s2 << 4);
+            }
+            Instruction::AddC { dst, s1, s2 } => {
+                bytecode.push(opcodes::ADDC);
+                bytecode.push((dst << 4) | s1);
+                bytecode.push(s2 << 4);
+            }
+            Instruction::SubC { dst, s1, s2 } => {
+                bytecode.push(opcodes::SUBC);
+                bytecode.push((dst << 4) | s1);
+                bytecode.push(s2 << 4);
+            }
+            Instruction::MulC { dst, s1, s2 } => {
+                bytecode.push(opcodes::MULC);
+                bytecode.push((dst << 4) | s1);
+                bytecode.push(s2 << 4);
+            }
+            Instruction::MulHi { dst, s1, s2 } => {
+                bytecode.push(opcodes::MULHI);
+                bytecode.push((dst << 4) | s1);
+                bytecode.push(s2 << 4);
+            }
+            Instruction::Sar { dst, s1, s2 } => {
+                bytecode.push(opcodes::SAR);
+                bytecode.push((dst << 4) | s1);
+                bytecode.push(s2 << 4);
+            }
+            Instruction::SLt { dst, s1, s2 } => {
+                bytecode.push(opcodes::SLT);
+                bytecode.push((dst << 4) | s1);
+                bytecode.push(s2 << 4);
+            }
+            Instruction::SGt { dst, s1, s2 } => {
+                bytecode.push(opcodes::SGT);
+                bytecode.push((dst << 4) | s1);
+                bytecode.push(s2 << 4);
+            }
             Instruction::Eq { dst, s1, s2 } => {
                 bytecode.push(opcodes::EQ);
                 bytecode.push((dst << 4) | s1);
@@ -346,6 +520,87 @@ impl Compiler {
                 bytecode.push((dst << 4) | src);
                 bytecode.push(len << 4);
             }
+            Instruction::CallDataCopy { dst_off, off, len } => {
+                bytecode.push(opcodes::CALLDATACOPY);
+                bytecode.push((dst_off << 4) | off);
+                bytecode.push(len << 4);
+            }
+            Instruction::AddrEq { dst, a, b } => {
+                bytecode.push(opcodes::ADDREQ);
+                bytecode.push((dst << 4) | a);
+                bytecode.push(b << 4);
+            }
+            Instruction::Hash { dst_off, off, len } => {
+                bytecode.push(opcodes::HASH);
+                bytecode.push((dst_off << 4) | off);
+                bytecode.push(len << 4);
+            }
+            Instruction::Hash64 { dst, off, len } => {
+                bytecode.push(opcodes::HASH64);
+                bytecode.push((dst << 4) | off);
+                bytecode.push(len << 4);
+            }
+
+            // Packed register lists
+            Instruction::Call {
+                dst,
+                addr,
+                value,
+                gas,
+                in_off,
+                in_len,
+                out_off,
+                out_len,
+            } => {
+                bytecode.push(opcodes::CALL);
+                emit_packed_registers(
+                    bytecode,
+                    &[
+                        *dst, *addr, *value, *gas, *in_off, *in_len, *out_off, *out_len,
+                    ],
+                );
+            }
+
+            Instruction::SigVerify {
+                dst,
+                pubkey,
+                msg_off,
+                msg_len,
+                sig,
+            } => {
+                bytecode.push(opcodes::SIGVERIFY);
+                emit_packed_registers(bytecode, &[*dst, *pubkey, *msg_off, *msg_len, *sig]);
+            }
+            Instruction::Create {
+                dst,
+                addr_off,
+                off,
+                len,
+                value,
+            } => {
+                bytecode.push(opcodes::CREATE);
+                emit_packed_registers(bytecode, &[*dst, *addr_off, *off, *len, *value]);
+            }
+            Instruction::Syscall {
+                dst,
+                id,
+                off,
+                len,
+                out,
+            } => {
+                bytecode.push(opcodes::SYSCALL);
+                emit_packed_registers(bytecode, &[*dst, *id, *off, *len, *out]);
+            }
+            Instruction::LogN {
+                offset,
+                len,
+                topics,
+            } => {
+                bytecode.push(opcodes::LOG0 + topics.len() as u8);
+                let mut regs = vec![*offset, *len];
+                regs.extend_from_slice(topics);
+                emit_packed_registers(bytecode, &regs);
+            }
 
             // Register + immediate
             Instruction::LoadI { dst, value } => {
@@ -354,16 +609,9 @@ impl Compiler {
                 bytecode.extend_from_slice(&value.to_le_bytes());
             }
             Instruction::LoadILabel { dst, label } => {
-                // Resolve label to address
-                let addr = self
-                    .symbol_table
-                    .get(label)
-                    .or_else(|| self.constants.get(label))
-                    .ok_or_else(|| CompileError::UndefinedLabel(label.clone()))?;
-
                 bytecode.push(opcodes::LOADI);
                 bytecode.push(dst << 4);
-                bytecode.extend_from_slice(&addr.to_le_bytes());
+                bytecode.extend_from_slice(&self.resolve(label)?.to_le_bytes());
             }
             Instruction::AddI { dst, src, imm } => {
                 bytecode.push(opcodes::ADDI);
@@ -374,6 +622,23 @@ impl Compiler {
 
         Ok(())
     }
+
+    /// Resolve a label to its address, or a `.const` to its value
+    fn resolve(&self, label: &str) -> Result<u64> {
+        self.symbol_table
+            .get(label)
+            .or_else(|| self.constants.get(label))
+            .copied()
+            .ok_or_else(|| CompileError::UndefinedLabel(label.to_string()))
+    }
+}
+
+/// Emit register operands packed two per byte (high nibble first).
+fn emit_packed_registers(bytecode: &mut Vec<u8>, regs: &[u8]) {
+    for pair in regs.chunks(2) {
+        let low = pair.get(1).copied().unwrap_or(0);
+        bytecode.push((pair[0] << 4) | low);
+    }
 }
 
 impl Default for Compiler {
@@ -480,6 +745,192 @@ mod tests {
         assert_eq!(bytecode[2], 100); // Constant value
     }
 
+    #[test]
+    fn test_compile_call() {
+        let source = "CALL R0, R1, R2, R3, R4, R5, R6, R7";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x04, 0x01, 0x23, 0x45, 0x67]);
+    }
+
+    #[test]
+    fn test_compile_subroutine_call() {
+        let source = r#"
+                JSUB helper
+                HALT
+            helper:
+                JRET
+                RET
+        "#;
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        // JSUB label carries the target as an immediate; RET still halts.
+        let mut expected = vec![0x09];
+        expected.extend_from_slice(&10u64.to_le_bytes());
+        expected.extend_from_slice(&[0x00, 0x08, 0x05]);
+        assert_eq!(bytecode, expected);
+    }
+
+    #[test]
+    fn test_compile_return() {
+        let source = "RETURN R1, R2";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x06, 0x12]);
+    }
+
+    #[test]
+    fn test_compile_log_topics() {
+        let source = r#"
+            LOG0 R0, R1
+            LOG1 R0, R1, R2
+            LOG4 R0, R1, R2, R3, R4, R5
+        "#;
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(
+            bytecode,
+            vec![0xA0, 0x01, 0xA1, 0x01, 0x20, 0xA4, 0x01, 0x23, 0x45]
+        );
+    }
+
+    #[test]
+    fn test_compile_memory_storage_ops() {
+        let source = "SLOADM R1, R2\nSSTOREM R3, R4";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x52, 0x12, 0x53, 0x34]);
+    }
+
+    #[test]
+    fn test_compile_transient_storage_ops() {
+        let source = "TLOAD R1, R2\nTSTORE R3, R4";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x54, 0x12, 0x55, 0x34]);
+    }
+
+    #[test]
+    fn test_compile_hash() {
+        let source = "HASH R1, R2, R3\nHASH64 R4, R5, R6";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x60, 0x12, 0x30, 0x61, 0x45, 0x60]);
+    }
+
+    #[test]
+    fn test_compile_full_address_ops() {
+        let source = "CALLERM R1\nADDRESSM R2\nADDREQ R3, R1, R2";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x86, 0x10, 0x87, 0x20, 0x88, 0x31, 0x20]);
+    }
+
+    #[test]
+    fn test_compile_balance_and_transfer() {
+        let source = "BALANCE R1, R2\nSELFBALANCE R3\nTRANSFER R4, R5";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x89, 0x12, 0x8A, 0x30, 0x90, 0x45]);
+    }
+
+    #[test]
+    fn test_compile_block_and_tx_context() {
+        let source = "CHAINID R1\nORIGIN R2\nGASPRICE R3\nCOINBASE R4\nBLOCKHASH R5, R6";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(
+            bytecode,
+            vec![0x8B, 0x10, 0x8C, 0x20, 0x8D, 0x30, 0x8E, 0x40, 0x8F, 0x56]
+        );
+    }
+
+    #[test]
+    fn test_compile_signed_and_checked_arithmetic() {
+        let source = r#"
+            SDIV R1, R2, R3
+            SMOD R1, R2, R3
+            ADDC R1, R2, R3
+            SUBC R1, R2, R3
+            MULC R1, R2, R3
+            MULHI R1, R2, R3
+            SAR R1, R2, R3
+            SLT R1, R2, R3
+            SGT R1, R2, R3
+        "#;
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        let opcodes: Vec<u8> = bytecode.chunks(3).map(|chunk| chunk[0]).collect();
+        assert_eq!(
+            opcodes,
+            vec![0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x26, 0x37, 0x38]
+        );
+        assert_eq!(&bytecode[..3], &[0x16, 0x12, 0x30]);
+    }
+
+    #[test]
+    fn test_compile_revert_with_data() {
+        let source = r#"
+            REVERT
+            REVERT R1, R2
+        "#;
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x0F, 0x0E, 0x12]);
+    }
+
+    #[test]
+    fn test_compile_calldata() {
+        let source = r#"
+            CALLDATASIZE R0
+            CALLDATALOAD R1, R2
+            CALLDATACOPY R3, R4, R5
+        "#;
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x46, 0x00, 0x47, 0x12, 0x48, 0x34, 0x50]);
+    }
+
+    #[test]
+    fn test_compile_sigverify() {
+        let source = "SIGVERIFY R0, R1, R2, R3, R4";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x62, 0x01, 0x23, 0x40]);
+    }
+
+    #[test]
+    fn test_compile_create() {
+        let source = "CREATE R5, R1, R2, R3, R4";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x91, 0x51, 0x23, 0x40]);
+    }
+
+    #[test]
+    fn test_compile_syscall() {
+        let source = "SYSCALL R1, R2, R3, R4, R5";
+        let program = Parser::parse(source).unwrap();
+        let bytecode = Compiler::compile(&program).unwrap();
+
+        assert_eq!(bytecode, vec![0x92, 0x12, 0x34, 0x50]);
+    }
+
     #[test]
     fn test_forward_reference() {
         let source = r#"
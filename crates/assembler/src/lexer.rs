@@ -27,6 +27,15 @@ pub enum Token {
     #[token("RET", ignore(ascii_case))]
     Ret,
 
+    #[token("JSUB", ignore(ascii_case))]
+    JSub,
+
+    #[token("JRET", ignore(ascii_case))]
+    JRet,
+
+    #[token("RETURN", ignore(ascii_case))]
+    Return,
+
     #[token("REVERT", ignore(ascii_case))]
     Revert,
 
@@ -49,6 +58,24 @@ pub enum Token {
     #[token("ADDI", ignore(ascii_case))]
     AddI,
 
+    #[token("SDIV", ignore(ascii_case))]
+    SDiv,
+
+    #[token("SMOD", ignore(ascii_case))]
+    SMod,
+
+    #[token("ADDC", ignore(ascii_case))]
+    AddC,
+
+    #[token("SUBC", ignore(ascii_case))]
+    SubC,
+
+    #[token("MULC", ignore(ascii_case))]
+    MulC,
+
+    #[token("MULHI", ignore(ascii_case))]
+    MulHi,
+
     // ========== Bitwise Instructions ==========
     #[token("AND", ignore(ascii_case))]
     And,
@@ -68,6 +95,9 @@ pub enum Token {
     #[token("SHR", ignore(ascii_case))]
     Shr,
 
+    #[token("SAR", ignore(ascii_case))]
+    Sar,
+
     // ========== Comparison Instructions ==========
     #[token("EQ", ignore(ascii_case))]
     Eq,
@@ -87,6 +117,12 @@ pub enum Token {
     #[token("GE", ignore(ascii_case))]
     Ge,
 
+    #[token("SLT", ignore(ascii_case))]
+    SLt,
+
+    #[token("SGT", ignore(ascii_case))]
+    SGt,
+
     #[token("ISZERO", ignore(ascii_case))]
     IsZero,
 
@@ -109,6 +145,15 @@ pub enum Token {
     #[token("MCOPY", ignore(ascii_case))]
     MCopy,
 
+    #[token("CALLDATASIZE", ignore(ascii_case))]
+    CallDataSize,
+
+    #[token("CALLDATALOAD", ignore(ascii_case))]
+    CallDataLoad,
+
+    #[token("CALLDATACOPY", ignore(ascii_case))]
+    CallDataCopy,
+
     // ========== Storage Instructions ==========
     #[token("SLOAD", ignore(ascii_case))]
     SLoad,
@@ -116,6 +161,28 @@ pub enum Token {
     #[token("SSTORE", ignore(ascii_case))]
     SStore,
 
+    #[token("SLOADM", ignore(ascii_case))]
+    SLoadM,
+
+    #[token("SSTOREM", ignore(ascii_case))]
+    SStoreM,
+
+    #[token("TLOAD", ignore(ascii_case))]
+    TLoad,
+
+    #[token("TSTORE", ignore(ascii_case))]
+    TStore,
+
+    // ========== Crypto Instructions ==========
+    #[token("HASH", ignore(ascii_case))]
+    Hash,
+
+    #[token("HASH64", ignore(ascii_case))]
+    Hash64,
+
+    #[token("SIGVERIFY", ignore(ascii_case))]
+    SigVerify,
+
     // ========== Immediate Instructions ==========
     #[token("LOADI", ignore(ascii_case))]
     LoadI,
@@ -142,6 +209,62 @@ pub enum Token {
     #[token("GAS", ignore(ascii_case))]
     Gas,
 
+    #[token("CALLERM", ignore(ascii_case))]
+    CallerM,
+
+    #[token("ADDRESSM", ignore(ascii_case))]
+    AddressM,
+
+    #[token("ADDREQ", ignore(ascii_case))]
+    AddrEq,
+
+    #[token("BALANCE", ignore(ascii_case))]
+    Balance,
+
+    #[token("SELFBALANCE", ignore(ascii_case))]
+    SelfBalance,
+
+    #[token("CHAINID", ignore(ascii_case))]
+    ChainId,
+
+    #[token("ORIGIN", ignore(ascii_case))]
+    Origin,
+
+    #[token("GASPRICE", ignore(ascii_case))]
+    GasPrice,
+
+    #[token("COINBASE", ignore(ascii_case))]
+    Coinbase,
+
+    #[token("BLOCKHASH", ignore(ascii_case))]
+    BlockHash,
+
+    // ========== System Instructions ==========
+    #[token("TRANSFER", ignore(ascii_case))]
+    Transfer,
+
+    #[token("CREATE", ignore(ascii_case))]
+    Create,
+
+    #[token("SYSCALL", ignore(ascii_case))]
+    Syscall,
+
+    // ========== Event Instructions ==========
+    #[token("LOG0", ignore(ascii_case))]
+    Log0,
+
+    #[token("LOG1", ignore(ascii_case))]
+    Log1,
+
+    #[token("LOG2", ignore(ascii_case))]
+    Log2,
+
+    #[token("LOG3", ignore(ascii_case))]
+    Log3,
+
+    #[token("LOG4", ignore(ascii_case))]
+    Log4,
+
     // ========== Debug Instructions ==========
     #[token("LOG", ignore(ascii_case))]
     Log,
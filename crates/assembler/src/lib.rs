@@ -37,6 +37,7 @@
 //!
 //! - **60+ instructions** - Full VM instruction set support
 //! - **Labels** - Symbolic jump targets
+//! - **Subroutines** - `JSUB label` and `JRET`
 //! - **Directives** - `.entry` for entry points, `.const` for constants
 //! - **Comments** - Semicolon-style comments
 //! - **Case-insensitive** - Instructions can be uppercase or lowercase
@@ -46,6 +47,7 @@ pub mod compiler;
 pub mod lexer;
 pub mod parser;
 
+use std::collections::HashMap;
 use thiserror::Error;
 
 /// Assembler errors
@@ -111,6 +113,31 @@ pub fn assemble_with_ast(source: &str) -> Result<(parser::Program, Vec<u8>)> {
     Ok((program, bytecode))
 }
 
+/// Assemble source code and return the address of every label
+///
+/// The label map serves as a simple source map, e.g. for setting debugger
+/// breakpoints by name.
+///
+/// # Example
+///
+/// ```
+/// use minichain_assembler::assemble_with_labels;
+///
+/// let (bytecode, labels) = assemble_with_labels("main:\nLOADI R0, 1\ndone:\nHALT").unwrap();
+/// assert_eq!(labels["main"], 0);
+/// assert_eq!(labels["done"], 10);
+/// assert_eq!(bytecode.len(), 11);
+/// ```
+pub fn assemble_with_labels(source: &str) -> Result<(Vec<u8>, HashMap<String, usize>)> {
+    let program = parser::Parser::parse(source)?;
+    let (bytecode, symbols) = compiler::Compiler::compile_with_labels(&program)?;
+    let labels = symbols
+        .into_iter()
+        .map(|(name, addr)| (name, addr as usize))
+        .collect();
+    Ok((bytecode, labels))
+}
+
 // Re-export commonly used types
 pub use compiler::{CompileError, Compiler};
 pub use lexer::{Lexer, Token};
@@ -57,64 +57,368 @@ pub enum Instruction {
     // Control flow
     Halt,
     Nop,
-    Jump { target: u8 },
-    JumpI { cond: u8, target: u8 },
-    Call { target: u8 },
+    Jump {
+        target: u8,
+    },
+    JumpI {
+        cond: u8,
+        target: u8,
+    },
+    Call {
+        dst: u8,
+        addr: u8,
+        value: u8,
+        gas: u8,
+        in_off: u8,
+        in_len: u8,
+        out_off: u8,
+        out_len: u8,
+    },
+    JSub {
+        target: u8,
+    },
+    /// `JSUB label`: JSUBI with the label's address as its immediate.
+    JSubLabel {
+        label: String,
+    },
+    JRet,
     Ret,
+    Return {
+        offset: u8,
+        len: u8,
+    },
     Revert,
+    RevertData {
+        offset: u8,
+        len: u8,
+    },
 
     // Arithmetic
-    Add { dst: u8, s1: u8, s2: u8 },
-    Sub { dst: u8, s1: u8, s2: u8 },
-    Mul { dst: u8, s1: u8, s2: u8 },
-    Div { dst: u8, s1: u8, s2: u8 },
-    Mod { dst: u8, s1: u8, s2: u8 },
-    AddI { dst: u8, src: u8, imm: u64 },
+    Add {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Sub {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Mul {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Div {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Mod {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    AddI {
+        dst: u8,
+        src: u8,
+        imm: u64,
+    },
+    SDiv {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    SMod {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    AddC {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    SubC {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    MulC {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    MulHi {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
 
     // Bitwise
-    And { dst: u8, s1: u8, s2: u8 },
-    Or { dst: u8, s1: u8, s2: u8 },
-    Xor { dst: u8, s1: u8, s2: u8 },
-    Not { dst: u8, src: u8 },
-    Shl { dst: u8, s1: u8, s2: u8 },
-    Shr { dst: u8, s1: u8, s2: u8 },
+    And {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Or {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Xor {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Not {
+        dst: u8,
+        src: u8,
+    },
+    Shl {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Shr {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Sar {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
 
     // Comparison
-    Eq { dst: u8, s1: u8, s2: u8 },
-    Ne { dst: u8, s1: u8, s2: u8 },
-    Lt { dst: u8, s1: u8, s2: u8 },
-    Gt { dst: u8, s1: u8, s2: u8 },
-    Le { dst: u8, s1: u8, s2: u8 },
-    Ge { dst: u8, s1: u8, s2: u8 },
-    IsZero { dst: u8, src: u8 },
+    Eq {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Ne {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Lt {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Gt {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Le {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    Ge {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    SLt {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    SGt {
+        dst: u8,
+        s1: u8,
+        s2: u8,
+    },
+    IsZero {
+        dst: u8,
+        src: u8,
+    },
 
     // Memory
-    Load8 { dst: u8, addr: u8 },
-    Load64 { dst: u8, addr: u8 },
-    Store8 { addr: u8, src: u8 },
-    Store64 { addr: u8, src: u8 },
-    MSize { dst: u8 },
-    MCopy { dst: u8, src: u8, len: u8 },
+    Load8 {
+        dst: u8,
+        addr: u8,
+    },
+    Load64 {
+        dst: u8,
+        addr: u8,
+    },
+    Store8 {
+        addr: u8,
+        src: u8,
+    },
+    Store64 {
+        addr: u8,
+        src: u8,
+    },
+    MSize {
+        dst: u8,
+    },
+    MCopy {
+        dst: u8,
+        src: u8,
+        len: u8,
+    },
+    CallDataSize {
+        dst: u8,
+    },
+    CallDataLoad {
+        dst: u8,
+        off: u8,
+    },
+    CallDataCopy {
+        dst_off: u8,
+        off: u8,
+        len: u8,
+    },
 
     // Storage
-    SLoad { dst: u8, key: u8 },
-    SStore { key: u8, value: u8 },
+    SLoad {
+        dst: u8,
+        key: u8,
+    },
+    SStore {
+        key: u8,
+        value: u8,
+    },
+    SLoadM {
+        dst_off: u8,
+        key_off: u8,
+    },
+    SStoreM {
+        key_off: u8,
+        val_off: u8,
+    },
+    TLoad {
+        dst: u8,
+        key: u8,
+    },
+    TStore {
+        key: u8,
+        value: u8,
+    },
+
+    // Crypto
+    Hash {
+        dst_off: u8,
+        off: u8,
+        len: u8,
+    },
+    Hash64 {
+        dst: u8,
+        off: u8,
+        len: u8,
+    },
+    SigVerify {
+        dst: u8,
+        pubkey: u8,
+        msg_off: u8,
+        msg_len: u8,
+        sig: u8,
+    },
 
     // Immediate
-    LoadI { dst: u8, value: u64 },
-    LoadILabel { dst: u8, label: String },
-    Mov { dst: u8, src: u8 },
+    LoadI {
+        dst: u8,
+        value: u64,
+    },
+    LoadILabel {
+        dst: u8,
+        label: String,
+    },
+    Mov {
+        dst: u8,
+        src: u8,
+    },
 
     // Context
-    Caller { dst: u8 },
-    CallValue { dst: u8 },
-    Address { dst: u8 },
-    BlockNumber { dst: u8 },
-    Timestamp { dst: u8 },
-    Gas { dst: u8 },
+    Caller {
+        dst: u8,
+    },
+    CallValue {
+        dst: u8,
+    },
+    Address {
+        dst: u8,
+    },
+    BlockNumber {
+        dst: u8,
+    },
+    Timestamp {
+        dst: u8,
+    },
+    Gas {
+        dst: u8,
+    },
+    CallerM {
+        off: u8,
+    },
+    AddressM {
+        off: u8,
+    },
+    AddrEq {
+        dst: u8,
+        a: u8,
+        b: u8,
+    },
+    Balance {
+        dst: u8,
+        addr: u8,
+    },
+    SelfBalance {
+        dst: u8,
+    },
+    ChainId {
+        dst: u8,
+    },
+    Origin {
+        off: u8,
+    },
+    GasPrice {
+        dst: u8,
+    },
+    Coinbase {
+        off: u8,
+    },
+    BlockHash {
+        off: u8,
+        number: u8,
+    },
+
+    // System
+    Transfer {
+        addr: u8,
+        amount: u8,
+    },
+    Create {
+        dst: u8,
+        addr_off: u8,
+        off: u8,
+        len: u8,
+        value: u8,
+    },
+    Syscall {
+        dst: u8,
+        id: u8,
+        off: u8,
+        len: u8,
+        out: u8,
+    },
+
+    // Events (LOG0-LOG4, selected by the number of topics)
+    LogN {
+        offset: u8,
+        len: u8,
+        topics: Vec<u8>,
+    },
 
     // Debug
-    Log { src: u8 },
+    Log {
+        src: u8,
+    },
 }
 
 impl Instruction {
@@ -122,19 +426,31 @@ impl Instruction {
     pub fn byte_size(&self) -> usize {
         match self {
             // No operands (1 byte: opcode)
-            Instruction::Halt | Instruction::Nop | Instruction::Ret | Instruction::Revert => 1,
+            Instruction::Halt
+            | Instruction::Nop
+            | Instruction::Ret
+            | Instruction::JRet
+            | Instruction::Revert => 1,
 
             // Single register (2 bytes: opcode + register)
             Instruction::Jump { .. }
-            | Instruction::Call { .. }
+            | Instruction::JSub { .. }
             | Instruction::Not { .. }
             | Instruction::MSize { .. }
+            | Instruction::CallDataSize { .. }
             | Instruction::Caller { .. }
             | Instruction::CallValue { .. }
             | Instruction::Address { .. }
             | Instruction::BlockNumber { .. }
             | Instruction::Timestamp { .. }
             | Instruction::Gas { .. }
+            | Instruction::CallerM { .. }
+            | Instruction::AddressM { .. }
+            | Instruction::SelfBalance { .. }
+            | Instruction::ChainId { .. }
+            | Instruction::Origin { .. }
+            | Instruction::GasPrice { .. }
+            | Instruction::Coinbase { .. }
             | Instruction::Log { .. } => 2,
 
             // Two registers (2 bytes: opcode + packed registers)
@@ -142,11 +458,21 @@ impl Instruction {
             | Instruction::Mov { .. }
             | Instruction::Load8 { .. }
             | Instruction::Load64 { .. }
+            | Instruction::CallDataLoad { .. }
             | Instruction::Store8 { .. }
             | Instruction::Store64 { .. }
             | Instruction::SLoad { .. }
             | Instruction::SStore { .. }
-            | Instruction::IsZero { .. } => 2,
+            | Instruction::SLoadM { .. }
+            | Instruction::SStoreM { .. }
+            | Instruction::TLoad { .. }
+            | Instruction::TStore { .. }
+            | Instruction::Balance { .. }
+            | Instruction::BlockHash { .. }
+            | Instruction::Transfer { .. }
+            | Instruction::IsZero { .. }
+            | Instruction::Return { .. }
+            | Instruction::RevertData { .. } => 2,
 
             // Three registers (3 bytes: opcode + 2 bytes packed registers)
             Instruction::Add { .. }
@@ -159,18 +485,45 @@ impl Instruction {
             | Instruction::Xor { .. }
             | Instruction::Shl { .. }
             | Instruction::Shr { .. }
+            | Instruction::SDiv { .. }
+            | Instruction::SMod { .. }
+            | Instruction::AddC { .. }
+            | Instruction::SubC { .. }
+            | Instruction::MulC { .. }
+            | Instruction::MulHi { .. }
+            | Instruction::Sar { .. }
+            | Instruction::SLt { .. }
+            | Instruction::SGt { .. }
             | Instruction::Eq { .. }
             | Instruction::Ne { .. }
             | Instruction::Lt { .. }
             | Instruction::Gt { .. }
             | Instruction::Le { .. }
             | Instruction::Ge { .. }
-            | Instruction::MCopy { .. } => 3,
+            | Instruction::MCopy { .. }
+            | Instruction::CallDataCopy { .. }
+            | Instruction::Hash { .. }
+            | Instruction::Hash64 { .. }
+            | Instruction::AddrEq { .. } => 3,
+
+            // Five registers (4 bytes: opcode + 3 bytes packed registers)
+            Instruction::SigVerify { .. }
+            | Instruction::Create { .. }
+            | Instruction::Syscall { .. } => 4,
+
+            // Eight registers (5 bytes: opcode + 4 bytes packed registers)
+            Instruction::Call { .. } => 5,
+
+            // Data range + topics (opcode + packed registers)
+            Instruction::LogN { topics, .. } => 1 + (2 + topics.len()).div_ceil(2),
 
             // Register + immediate (10 bytes: opcode + register + u64)
             Instruction::LoadI { .. }
             | Instruction::LoadILabel { .. }
             | Instruction::AddI { .. } => 10,
+
+            // Immediate only (9 bytes: opcode + u64)
+            Instruction::JSubLabel { .. } => 9,
         }
     }
 }
@@ -288,16 +641,59 @@ impl<'source> Parser<'source> {
             Token::Halt => Ok(Instruction::Halt),
             Token::Nop => Ok(Instruction::Nop),
             Token::Ret => Ok(Instruction::Ret),
-            Token::Revert => Ok(Instruction::Revert),
+            Token::JRet => Ok(Instruction::JRet),
+            Token::Revert => {
+                // REVERT takes an optional `offset, len` payload.
+                if !matches!(self.peek().0, Token::Register(_)) {
+                    return Ok(Instruction::Revert);
+                }
+                let offset = self.expect_register()?;
+                self.expect_comma()?;
+                let len = self.expect_register()?;
+                Ok(Instruction::RevertData { offset, len })
+            }
 
             // Single register
             Token::Jump => {
                 let target = self.expect_register()?;
                 Ok(Instruction::Jump { target })
             }
-            Token::Call => {
+            Token::JSub if matches!(self.peek().0, Token::Identifier(_)) => {
+                let label = self.expect_identifier()?;
+                Ok(Instruction::JSubLabel { label })
+            }
+            Token::JSub => {
                 let target = self.expect_register()?;
-                Ok(Instruction::Call { target })
+                Ok(Instruction::JSub { target })
+            }
+            Token::Call => {
+                let [dst, addr, value, gas, in_off, in_len, out_off, out_len] =
+                    self.expect_registers::<8>()?;
+                Ok(Instruction::Call {
+                    dst,
+                    addr,
+                    value,
+                    gas,
+                    in_off,
+                    in_len,
+                    out_off,
+                    out_len,
+                })
+            }
+            Token::Log0 => self.parse_log(0),
+            Token::Log1 => self.parse_log(1),
+            Token::Log2 => self.parse_log(2),
+            Token::Log3 => self.parse_log(3),
+            Token::Log4 => self.parse_log(4),
+            Token::SigVerify => {
+                let [dst, pubkey, msg_off, msg_len, sig] = self.expect_registers::<5>()?;
+                Ok(Instruction::SigVerify {
+                    dst,
+                    pubkey,
+                    msg_off,
+                    msg_len,
+                    sig,
+                })
             }
             Token::Log => {
                 let src = self.expect_register()?;
@@ -305,6 +701,12 @@ impl<'source> Parser<'source> {
             }
 
             // Two registers
+            Token::Return => {
+                let offset = self.expect_register()?;
+                self.expect_comma()?;
+                let len = self.expect_register()?;
+                Ok(Instruction::Return { offset, len })
+            }
             Token::JumpI => {
                 let cond = self.expect_register()?;
                 self.expect_comma()?;
@@ -365,10 +767,44 @@ impl<'source> Parser<'source> {
                 let value = self.expect_register()?;
                 Ok(Instruction::SStore { key, value })
             }
+            Token::SLoadM => {
+                let dst_off = self.expect_register()?;
+                self.expect_comma()?;
+                let key_off = self.expect_register()?;
+                Ok(Instruction::SLoadM { dst_off, key_off })
+            }
+            Token::SStoreM => {
+                let key_off = self.expect_register()?;
+                self.expect_comma()?;
+                let val_off = self.expect_register()?;
+                Ok(Instruction::SStoreM { key_off, val_off })
+            }
+            Token::TLoad => {
+                let dst = self.expect_register()?;
+                self.expect_comma()?;
+                let key = self.expect_register()?;
+                Ok(Instruction::TLoad { dst, key })
+            }
+            Token::TStore => {
+                let key = self.expect_register()?;
+                self.expect_comma()?;
+                let value = self.expect_register()?;
+                Ok(Instruction::TStore { key, value })
+            }
             Token::MSize => {
                 let dst = self.expect_register()?;
                 Ok(Instruction::MSize { dst })
             }
+            Token::CallDataSize => {
+                let dst = self.expect_register()?;
+                Ok(Instruction::CallDataSize { dst })
+            }
+            Token::CallDataLoad => {
+                let dst = self.expect_register()?;
+                self.expect_comma()?;
+                let off = self.expect_register()?;
+                Ok(Instruction::CallDataLoad { dst, off })
+            }
             Token::Caller => {
                 let dst = self.expect_register()?;
                 Ok(Instruction::Caller { dst })
@@ -393,6 +829,72 @@ impl<'source> Parser<'source> {
                 let dst = self.expect_register()?;
                 Ok(Instruction::Gas { dst })
             }
+            Token::CallerM => {
+                let off = self.expect_register()?;
+                Ok(Instruction::CallerM { off })
+            }
+            Token::AddressM => {
+                let off = self.expect_register()?;
+                Ok(Instruction::AddressM { off })
+            }
+            Token::SelfBalance => {
+                let dst = self.expect_register()?;
+                Ok(Instruction::SelfBalance { dst })
+            }
+            Token::ChainId => {
+                let dst = self.expect_register()?;
+                Ok(Instruction::ChainId { dst })
+            }
+            Token::Origin => {
+                let off = self.expect_register()?;
+                Ok(Instruction::Origin { off })
+            }
+            Token::GasPrice => {
+                let dst = self.expect_register()?;
+                Ok(Instruction::GasPrice { dst })
+            }
+            Token::Coinbase => {
+                let off = self.expect_register()?;
+                Ok(Instruction::Coinbase { off })
+            }
+            Token::BlockHash => {
+                let off = self.expect_register()?;
+                self.expect_comma()?;
+                let number = self.expect_register()?;
+                Ok(Instruction::BlockHash { off, number })
+            }
+            Token::Balance => {
+                let dst = self.expect_register()?;
+                self.expect_comma()?;
+                let addr = self.expect_register()?;
+                Ok(Instruction::Balance { dst, addr })
+            }
+            Token::Transfer => {
+                let addr = self.expect_register()?;
+                self.expect_comma()?;
+                let amount = self.expect_register()?;
+                Ok(Instruction::Transfer { addr, amount })
+            }
+            Token::Create => {
+                let [dst, addr_off, off, len, value] = self.expect_registers::<5>()?;
+                Ok(Instruction::Create {
+                    dst,
+                    addr_off,
+                    off,
+                    len,
+                    value,
+                })
+            }
+            Token::Syscall => {
+                let [dst, id, off, len, out] = self.expect_registers::<5>()?;
+                Ok(Instruction::Syscall {
+                    dst,
+                    id,
+                    off,
+                    len,
+                    out,
+                })
+            }
 
             // Three registers
             Token::Add => self.parse_three_reg(|d, s1, s2| Instruction::Add { dst: d, s1, s2 }),
@@ -405,6 +907,15 @@ impl<'source> Parser<'source> {
             Token::Xor => self.parse_three_reg(|d, s1, s2| Instruction::Xor { dst: d, s1, s2 }),
             Token::Shl => self.parse_three_reg(|d, s1, s2| Instruction::Shl { dst: d, s1, s2 }),
             Token::Shr => self.parse_three_reg(|d, s1, s2| Instruction::Shr { dst: d, s1, s2 }),
+            Token::SDiv => self.parse_three_reg(|d, s1, s2| Instruction::SDiv { dst: d, s1, s2 }),
+            Token::SMod => self.parse_three_reg(|d, s1, s2| Instruction::SMod { dst: d, s1, s2 }),
+            Token::AddC => self.parse_three_reg(|d, s1, s2| Instruction::AddC { dst: d, s1, s2 }),
+            Token::SubC => self.parse_three_reg(|d, s1, s2| Instruction::SubC { dst: d, s1, s2 }),
+            Token::MulC => self.parse_three_reg(|d, s1, s2| Instruction::MulC { dst: d, s1, s2 }),
+            Token::MulHi => self.parse_three_reg(|d, s1, s2| Instruction::MulHi { dst: d, s1, s2 }),
+            Token::Sar => self.parse_three_reg(|d, s1, s2| Instruction::Sar { dst: d, s1, s2 }),
+            Token::SLt => self.parse_three_reg(|d, s1, s2| Instruction::SLt { dst: d, s1, s2 }),
+            Token::SGt => self.parse_three_reg(|d, s1, s2| Instruction::SGt { dst: d, s1, s2 }),
             Token::Eq => self.parse_three_reg(|d, s1, s2| Instruction::Eq { dst: d, s1, s2 }),
             Token::Ne => self.parse_three_reg(|d, s1, s2| Instruction::Ne { dst: d, s1, s2 }),
             Token::Lt => self.parse_three_reg(|d, s1, s2| Instruction::Lt { dst: d, s1, s2 }),
@@ -416,6 +927,22 @@ impl<'source> Parser<'source> {
                 src: s,
                 len: l,
             }),
+            Token::CallDataCopy => self.parse_three_reg(|d, o, l| Instruction::CallDataCopy {
+                dst_off: d,
+                off: o,
+                len: l,
+            }),
+            Token::AddrEq => self.parse_three_reg(|d, a, b| Instruction::AddrEq { dst: d, a, b }),
+            Token::Hash => self.parse_three_reg(|d, o, l| Instruction::Hash {
+                dst_off: d,
+                off: o,
+                len: l,
+            }),
+            Token::Hash64 => self.parse_three_reg(|d, o, l| Instruction::Hash64 {
+                dst: d,
+                off: o,
+                len: l,
+            }),
 
             // Register + immediate
             Token::LoadI => {
@@ -475,6 +1002,35 @@ impl<'source> Parser<'source> {
         Ok(f(dst, s1, s2))
     }
 
+    /// Expect `N` comma-separated registers
+    fn expect_registers<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut regs = [0u8; N];
+        for (i, reg) in regs.iter_mut().enumerate() {
+            if i > 0 {
+                self.expect_comma()?;
+            }
+            *reg = self.expect_register()?;
+        }
+        Ok(regs)
+    }
+
+    /// Parse the operands of LOG0-LOG4: `offset, len` followed by the topics.
+    fn parse_log(&mut self, topic_count: usize) -> Result<Instruction> {
+        let offset = self.expect_register()?;
+        self.expect_comma()?;
+        let len = self.expect_register()?;
+        let mut topics = Vec::with_capacity(topic_count);
+        for _ in 0..topic_count {
+            self.expect_comma()?;
+            topics.push(self.expect_register()?);
+        }
+        Ok(Instruction::LogN {
+            offset,
+            len,
+            topics,
+        })
+    }
+
     /// Expect a register
     fn expect_register(&mut self) -> Result<u8> {
         let (token, line) = self.advance();
@@ -643,4 +1199,64 @@ mod tests {
         );
         assert_eq!(Instruction::LoadI { dst: 0, value: 10 }.byte_size(), 10);
     }
+
+    #[test]
+    fn test_parse_call() {
+        let source = "CALL R0, R1, R2, R3, R4, R5, R6, R7";
+        let program = Parser::parse(source).unwrap();
+
+        assert_eq!(
+            program.statements[0],
+            Statement::Instruction(Instruction::Call {
+                dst: 0,
+                addr: 1,
+                value: 2,
+                gas: 3,
+                in_off: 4,
+                in_len: 5,
+                out_off: 6,
+                out_len: 7,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_return() {
+        let source = "RETURN R1, R2";
+        let program = Parser::parse(source).unwrap();
+
+        assert_eq!(
+            program.statements[0],
+            Statement::Instruction(Instruction::Return { offset: 1, len: 2 })
+        );
+        assert_eq!(Instruction::Return { offset: 1, len: 2 }.byte_size(), 2);
+    }
+
+    #[test]
+    fn test_parse_subroutine_sugar() {
+        let source = "JSUB helper
+JSUB R3
+JRET
+RET";
+        let program = Parser::parse(source).unwrap();
+
+        assert_eq!(
+            program.statements,
+            vec![
+                Statement::Instruction(Instruction::JSubLabel {
+                    label: "helper".to_string()
+                }),
+                Statement::Instruction(Instruction::JSub { target: 3 }),
+                Statement::Instruction(Instruction::JRet),
+                Statement::Instruction(Instruction::Ret),
+            ]
+        );
+        assert_eq!(
+            Instruction::JSubLabel {
+                label: "helper".to_string()
+            }
+            .byte_size(),
+            9
+        );
+    }
 }
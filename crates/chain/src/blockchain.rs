@@ -2,13 +2,19 @@
 //!
 //! This module brings together all components: consensus, storage, mempool, and execution.
 
-use crate::executor::{BlockExecutionResult, ContractQuery, ContractQueryResult, Executor};
+use crate::executor::{
+    BlockExecutionResult, ContractQuery, ContractQueryResult, Executor, DEFAULT_CHAIN_ID,
+};
 use crate::mempool::{Mempool, MempoolConfig};
 use minichain_consensus::{
     Authority, BlockProposer, BlockValidator, PoAConfig, TransactionValidator,
 };
-use minichain_core::{Address, Block, BlockHeader, Hash, Transaction};
+use minichain_core::{Address, Block, BlockHeader, Hash, Log, Transaction};
 use minichain_storage::{ChainStore, StateManager, Storage};
+use minichain_vm::{GasSchedules, Inspector, ProgramCache, DEFAULT_MAX_RETURN_SIZE};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Errors that can occur during blockchain operations.
@@ -48,6 +54,12 @@ pub struct BlockchainConfig {
     pub consensus: PoAConfig,
     /// Maximum transactions per block.
     pub max_block_size: usize,
+    /// Maximum bytes a contract may return via RETURN.
+    pub max_return_size: usize,
+    /// Gas prices by activation height.
+    pub gas_schedules: GasSchedules,
+    /// Chain identifier exposed to contracts through CHAINID.
+    pub chain_id: u64,
 }
 
 impl Default for BlockchainConfig {
@@ -55,6 +67,9 @@ impl Default for BlockchainConfig {
         Self {
             consensus: PoAConfig::default(),
             max_block_size: 1000,
+            max_return_size: DEFAULT_MAX_RETURN_SIZE,
+            gas_schedules: GasSchedules::default(),
+            chain_id: DEFAULT_CHAIN_ID,
         }
     }
 }
@@ -74,6 +89,8 @@ pub struct Blockchain<'a> {
     authority: Authority,
     /// Configuration.
     config: BlockchainConfig,
+    /// Decoded contract code, reused across blocks and queries.
+    programs: Arc<ProgramCache>,
 }
 
 impl<'a> Blockchain<'a> {
@@ -91,6 +108,7 @@ impl<'a> Blockchain<'a> {
             mempool,
             authority,
             config,
+            programs: Arc::new(ProgramCache::new()),
         }
     }
 
@@ -123,6 +141,11 @@ impl<'a> Blockchain<'a> {
         Ok(self.chain.get_block_by_height(height)?)
     }
 
+    /// Get the event logs of a block, grouped per transaction in block order.
+    pub fn get_block_logs(&self, hash: &Hash) -> Result<Option<Vec<Vec<Log>>>> {
+        Ok(self.chain.get_block_logs(hash)?)
+    }
+
     /// Register a public key for an authority.
     pub fn register_authority(&mut self, address: Address, public_key: minichain_core::PublicKey) {
         self.authority.register_public_key(address, public_key);
@@ -199,12 +222,22 @@ impl<'a> Blockchain<'a> {
         BlockValidator::validate_full(block, parent.hash(), parent.header.height)?;
 
         // Execute transactions
-        let executor = Executor::new(&self.state);
+        let executor = self.executor();
         let result = executor.execute_block(block)?;
 
         Ok(result)
     }
 
+    /// Executor configured with this chain's VM limits and gas schedules.
+    fn executor(&self) -> Executor<'_> {
+        Executor::new(&self.state)
+            .with_max_return_size(self.config.max_return_size)
+            .with_gas_schedules(self.config.gas_schedules.clone())
+            .with_program_cache(self.programs.clone())
+            .with_chain_id(self.config.chain_id)
+            .with_chain_store(&self.chain)
+    }
+
     /// Execute a read-only contract query against the current state.
     pub fn query_contract(
         &self,
@@ -213,9 +246,41 @@ impl<'a> Blockchain<'a> {
         data: &[u8],
         call_value: u64,
         gas_limit: u64,
+    ) -> Result<ContractQueryResult> {
+        self.run_query(
+            self.executor(),
+            contract,
+            caller,
+            data,
+            call_value,
+            gas_limit,
+        )
+    }
+
+    /// Execute a read-only contract query observed by `inspector`.
+    pub fn query_contract_with_inspector<'s>(
+        &'s self,
+        contract: &Address,
+        caller: Address,
+        data: &[u8],
+        call_value: u64,
+        gas_limit: u64,
+        inspector: Rc<RefCell<dyn Inspector + 's>>,
+    ) -> Result<ContractQueryResult> {
+        let executor = self.executor().with_inspector(inspector);
+        self.run_query(executor, contract, caller, data, call_value, gas_limit)
+    }
+
+    fn run_query(
+        &self,
+        executor: Executor<'_>,
+        contract: &Address,
+        caller: Address,
+        data: &[u8],
+        call_value: u64,
+        gas_limit: u64,
     ) -> Result<ContractQueryResult> {
         let latest_block = self.get_latest_block()?;
-        let executor = Executor::new(&self.state);
         Ok(executor.query_contract(
             contract,
             ContractQuery {
@@ -223,8 +288,7 @@ impl<'a> Blockchain<'a> {
                 data,
                 call_value,
                 gas_limit,
-                block_number: latest_block.header.height,
-                timestamp: latest_block.header.timestamp,
+                block: executor.block_context(&latest_block.header)?,
             },
         )?)
     }
@@ -240,8 +304,10 @@ impl<'a> Blockchain<'a> {
         let block_hash = block.hash();
         let block_height = block.header.height;
 
-        // Store block
+        // Store block and the events its transactions emitted
         self.chain.put_block(&block)?;
+        let logs: Vec<_> = result.receipts.iter().map(|r| r.logs.clone()).collect();
+        self.chain.put_block_logs(&block_hash, &logs)?;
 
         // Update chain head
         self.chain.set_head(&block_hash, block_height)?;
@@ -300,6 +366,9 @@ mod tests {
         let config = BlockchainConfig {
             consensus: PoAConfig::new(vec![addr], 5),
             max_block_size: 100,
+            max_return_size: DEFAULT_MAX_RETURN_SIZE,
+            gas_schedules: GasSchedules::default(),
+            chain_id: DEFAULT_CHAIN_ID,
         };
 
         let mut blockchain = Blockchain::new(storage, config);
@@ -389,6 +458,13 @@ mod tests {
         assert_eq!(blockchain.height().unwrap(), 1);
         assert_eq!(blockchain.mempool.len(), 0); // Transaction removed from mempool
         assert_eq!(blockchain.state.get_nonce(&from).unwrap(), 1); // Nonce incremented
+
+        // One (empty) log list is stored per transaction
+        let head = blockchain.get_latest_block().unwrap().hash();
+        assert_eq!(
+            blockchain.get_block_logs(&head).unwrap(),
+            Some(vec![vec![]])
+        );
     }
 
     #[test]
@@ -401,6 +477,9 @@ mod tests {
         let config = BlockchainConfig {
             consensus: PoAConfig::new(vec![addr], 5),
             max_block_size: 100,
+            max_return_size: DEFAULT_MAX_RETURN_SIZE,
+            gas_schedules: GasSchedules::default(),
+            chain_id: DEFAULT_CHAIN_ID,
         };
 
         // First instance: create blockchain and import block
@@ -2,17 +2,26 @@
 //!
 //! Executes transactions in blocks and updates the world state.
 
-use minichain_core::{Account, Address, Block, Hash, Transaction};
-use minichain_storage::StateManager;
-use minichain_vm::{StorageBackend, Vm, VmError};
+use crate::host::{load_program, ChainHost, OverlayStorage, PendingState};
+use crate::precompiles::Precompiles;
+use crate::syscalls::ChainSyscalls;
+use minichain_core::{Address, Block, BlockHeader, Hash, Log, Transaction};
+use minichain_storage::{ChainStore, StateManager};
+use minichain_vm::{
+    verify, BlockContext, ExecutionStatus, GasSchedules, Inspector, Program, ProgramCache,
+    TxContext, Vm, VmError, BLOCKHASH_WINDOW, DEFAULT_MAX_RETURN_SIZE,
+};
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Fixed-size deployment header that prefixes runtime bytecode length.
 const DEPLOY_HEADER_BYTES: usize = 4;
 
+/// Chain identifier exposed through CHAINID unless configured otherwise.
+pub const DEFAULT_CHAIN_ID: u64 = 1;
+
 /// Errors that can occur during execution.
 #[derive(Debug, Error)]
 pub enum ExecutionError {
@@ -51,10 +60,25 @@ pub struct TransactionReceipt {
     pub gas_used: u64,
     /// Contract address (if deployment).
     pub contract_address: Option<Address>,
+    /// Bytes returned by the contract (empty for transfers and deployments).
+    pub return_data: Vec<u8>,
+    /// Events emitted by successful contract execution.
+    pub logs: Vec<Log>,
+    /// Payload the contract passed to REVERT (empty otherwise).
+    pub revert_data: Vec<u8>,
+    /// How contract execution ended (`None` if no contract code ran).
+    pub status: Option<ExecutionStatus>,
     /// Error message (if failed).
     pub error: Option<String>,
 }
 
+impl TransactionReceipt {
+    /// The revert payload as text, if it is non-empty valid UTF-8.
+    pub fn revert_reason(&self) -> Option<&str> {
+        decode_revert_reason(&self.revert_data)
+    }
+}
+
 /// Result of executing a block.
 #[derive(Debug, Clone)]
 pub struct BlockExecutionResult {
@@ -76,34 +100,102 @@ pub struct ContractQueryResult {
     pub gas_used: u64,
     /// Raw return bytes read back from VM memory.
     pub return_data: Vec<u8>,
+    /// Events the query would have emitted.
+    pub logs: Vec<Log>,
+    /// Payload the contract passed to REVERT (empty otherwise).
+    pub revert_data: Vec<u8>,
+    /// How contract execution ended (`None` if there is no contract).
+    pub status: Option<ExecutionStatus>,
     /// Error message if execution failed.
     pub error: Option<String>,
 }
 
+impl ContractQueryResult {
+    /// The revert payload as text, if it is non-empty valid UTF-8.
+    pub fn revert_reason(&self) -> Option<&str> {
+        decode_revert_reason(&self.revert_data)
+    }
+}
+
+/// Decode a revert payload as UTF-8, ignoring empty or binary payloads.
+pub fn decode_revert_reason(data: &[u8]) -> Option<&str> {
+    if data.is_empty() {
+        return None;
+    }
+    std::str::from_utf8(data).ok()
+}
+
 /// Inputs for executing a read-only contract query.
 pub struct ContractQuery<'a> {
     /// Caller address exposed to the VM via `CALLER`.
     pub caller: Address,
-    /// Calldata loaded into VM memory before execution.
+    /// Calldata read through the CALLDATA* instructions.
     pub data: &'a [u8],
     /// Call value exposed via `CALLVALUE`.
     pub call_value: u64,
     /// Maximum gas available to the query execution.
     pub gas_limit: u64,
-    /// Current block number for context opcodes.
-    pub block_number: u64,
-    /// Current block timestamp for context opcodes.
-    pub timestamp: u64,
+    /// Block the query runs against, for context opcodes.
+    pub block: BlockContext,
 }
 
 #[derive(Debug, Clone)]
 struct VmExecution {
+    success: bool,
+    status: ExecutionStatus,
+    gas_used: u64,
+    return_data: Vec<u8>,
+    logs: Vec<Log>,
+    revert_data: Vec<u8>,
+    error: Option<String>,
+}
+
+/// Outcome of a transfer, deployment or call before gas is refunded.
+#[derive(Debug, Clone, Default)]
+struct TxOutcome {
     success: bool,
     gas_used: u64,
+    contract_address: Option<Address>,
     return_data: Vec<u8>,
+    logs: Vec<Log>,
+    revert_data: Vec<u8>,
+    status: Option<ExecutionStatus>,
     error: Option<String>,
 }
 
+impl TxOutcome {
+    fn failed(gas_used: u64, error: String) -> Self {
+        Self {
+            gas_used,
+            error: Some(error),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<VmExecution> for TxOutcome {
+    fn from(execution: VmExecution) -> Self {
+        Self {
+            success: execution.success,
+            gas_used: execution.gas_used,
+            contract_address: None,
+            return_data: execution.return_data,
+            logs: execution.logs,
+            revert_data: execution.revert_data,
+            status: Some(execution.status),
+            error: execution.error,
+        }
+    }
+}
+
+/// Transaction context of `tx`, for the ORIGIN and GASPRICE opcodes.
+fn tx_context(tx: &Transaction) -> TxContext {
+    TxContext {
+        origin: tx.from,
+        gas_price: tx.gas_price,
+    }
+}
+
 /// Encode deployment data as: `[runtime_len: u32][runtime_code][init_data]`.
 pub fn encode_deployment_payload(runtime_code: &[u8], init_data: &[u8]) -> Vec<u8> {
     let mut payload =
@@ -140,60 +232,121 @@ pub fn decode_deployment_payload(data: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
     ))
 }
 
-#[derive(Clone)]
-struct OverlayStorage<'a> {
+pub struct Executor<'a> {
+    /// State manager for account operations.
     state: &'a StateManager<'a>,
-    contract: Address,
-    writes: Rc<RefCell<HashMap<[u8; 32], [u8; 32]>>>,
+    /// Maximum bytes a contract may return via RETURN.
+    max_return_size: usize,
+    /// Gas prices by activation height.
+    gas_schedules: GasSchedules,
+    /// Inspector attached to every contract execution.
+    inspector: Option<Rc<RefCell<dyn Inspector + 'a>>>,
+    /// Decoded contract code shared across executions.
+    programs: Arc<ProgramCache>,
+    /// Native contracts at reserved addresses.
+    precompiles: Arc<Precompiles>,
+    /// Chain identifier exposed through CHAINID.
+    chain_id: u64,
+    /// Block history for BLOCKHASH; without it no hashes are available.
+    chain: Option<&'a ChainStore<'a>>,
 }
 
-impl<'a> OverlayStorage<'a> {
-    fn new(state: &'a StateManager<'a>, contract: Address) -> Self {
+impl<'a> Executor<'a> {
+    /// Create a new executor.
+    pub fn new(state: &'a StateManager<'a>) -> Self {
         Self {
             state,
-            contract,
-            writes: Rc::new(RefCell::new(HashMap::new())),
+            max_return_size: DEFAULT_MAX_RETURN_SIZE,
+            gas_schedules: GasSchedules::default(),
+            inspector: None,
+            programs: Arc::new(ProgramCache::new()),
+            precompiles: Arc::new(Precompiles::standard()),
+            chain_id: DEFAULT_CHAIN_ID,
+            chain: None,
         }
     }
-}
 
-impl StorageBackend for OverlayStorage<'_> {
-    fn sload(&self, key: &[u8; 32]) -> [u8; 32] {
-        if let Some(value) = self.writes.borrow().get(key) {
-            return *value;
-        }
-        self.state
-            .sload(&self.contract, key)
-            .expect("contract storage read should not fail")
+    /// Set the maximum return data size enforced on contract executions.
+    pub fn with_max_return_size(mut self, max_return_size: usize) -> Self {
+        self.max_return_size = max_return_size;
+        self
     }
 
-    fn sstore(&mut self, key: &[u8; 32], value: &[u8; 32]) {
-        self.writes.borrow_mut().insert(*key, *value);
+    /// Set the gas schedules used to price contract execution.
+    pub fn with_gas_schedules(mut self, gas_schedules: GasSchedules) -> Self {
+        self.gas_schedules = gas_schedules;
+        self
     }
-}
 
-pub struct Executor<'a> {
-    /// State manager for account operations.
-    state: &'a StateManager<'a>,
-}
+    /// Share decoded contract code with other executors.
+    pub fn with_program_cache(mut self, programs: Arc<ProgramCache>) -> Self {
+        self.programs = programs;
+        self
+    }
 
-impl<'a> Executor<'a> {
-    /// Create a new executor.
-    pub fn new(state: &'a StateManager<'a>) -> Self {
-        Self { state }
+    /// Replace the precompiled contracts, [`Precompiles::standard`] by default.
+    pub fn with_precompiles(mut self, precompiles: Arc<Precompiles>) -> Self {
+        self.precompiles = precompiles;
+        self
+    }
+
+    /// Set the chain identifier exposed through CHAINID.
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Read recent block hashes for BLOCKHASH from `chain`.
+    pub fn with_chain_store(mut self, chain: &'a ChainStore<'a>) -> Self {
+        self.chain = Some(chain);
+        self
+    }
+
+    /// Observe contract execution with an inspector, e.g. for tracing or profiling.
+    pub fn with_inspector(mut self, inspector: Rc<RefCell<dyn Inspector + 'a>>) -> Self {
+        self.inspector = Some(inspector);
+        self
+    }
+
+    /// Context of the block with `header`, for the VM's context opcodes.
+    ///
+    /// Hashes of up to [`BLOCKHASH_WINDOW`] preceding blocks are read from the
+    /// chain store, if one is configured.
+    pub fn block_context(&self, header: &BlockHeader) -> Result<BlockContext> {
+        let mut recent_hashes = Vec::new();
+        if let Some(chain) = self.chain {
+            let oldest = header.height.saturating_sub(BLOCKHASH_WINDOW);
+            for height in (oldest..header.height).rev() {
+                match chain.get_block_by_height(height)? {
+                    Some(block) => recent_hashes.push(block.hash()),
+                    None => break,
+                }
+            }
+            recent_hashes.reverse();
+        }
+        Ok(BlockContext {
+            number: header.height,
+            timestamp: header.timestamp,
+            author: header.author,
+            chain_id: self.chain_id,
+            recent_hashes: recent_hashes.into(),
+        })
     }
 
     /// Execute a single transaction.
     pub fn execute_transaction(&self, tx: &Transaction) -> Result<TransactionReceipt> {
-        self.execute_transaction_with_context(tx, 0, 0)
+        let block = BlockContext {
+            chain_id: self.chain_id,
+            ..BlockContext::default()
+        };
+        self.execute_transaction_with_context(tx, &block)
     }
 
     /// Execute a single transaction with explicit block context for VM opcodes.
     pub fn execute_transaction_with_context(
         &self,
         tx: &Transaction,
-        block_number: u64,
-        timestamp: u64,
+        block: &BlockContext,
     ) -> Result<TransactionReceipt> {
         let tx_hash = tx.hash();
         let sender = &tx.from;
@@ -208,6 +361,10 @@ impl<'a> Executor<'a> {
                 success: false,
                 gas_used: 0,
                 contract_address: None,
+                return_data: Vec::new(),
+                logs: Vec::new(),
+                revert_data: Vec::new(),
+                status: None,
                 error: Some(format!(
                     "invalid nonce: expected {}, got {}",
                     sender_account.nonce, tx.nonce
@@ -223,6 +380,10 @@ impl<'a> Executor<'a> {
                 success: false,
                 gas_used: 0,
                 contract_address: None,
+                return_data: Vec::new(),
+                logs: Vec::new(),
+                revert_data: Vec::new(),
+                status: None,
                 error: Some(format!(
                     "insufficient balance: required {}, available {}",
                     max_cost, sender_account.balance
@@ -238,10 +399,10 @@ impl<'a> Executor<'a> {
         self.state.sub_balance(sender, max_cost)?;
 
         // Execute based on transaction type
-        let (success, gas_used, contract_address, error) = if tx.is_deploy() {
-            self.execute_deploy(tx, block_number, timestamp)?
+        let outcome = if tx.is_deploy() {
+            self.execute_deploy(tx, block)?
         } else if tx.is_call() {
-            self.execute_call(tx, block_number, timestamp)?
+            self.execute_call(tx, block)?
         } else {
             // For transfer: just add value to recipient (sender already deducted in max_cost)
             self.execute_transfer_without_sender_deduction(tx)?
@@ -249,24 +410,31 @@ impl<'a> Executor<'a> {
 
         // Refund unused gas. Failed calls do not transfer call value, so only actual
         // gas spent is charged in that case.
-        let charged_value = if success { tx.value } else { 0 };
+        let charged_value = if outcome.success { tx.value } else { 0 };
         let refund = max_cost
             .saturating_sub(charged_value)
-            .saturating_sub(gas_used.saturating_mul(tx.gas_price));
+            .saturating_sub(outcome.gas_used.saturating_mul(tx.gas_price));
         if refund > 0 {
             self.state.add_balance(sender, refund)?;
         }
 
         Ok(TransactionReceipt {
             tx_hash,
-            success,
-            gas_used,
-            contract_address,
-            error,
+            success: outcome.success,
+            gas_used: outcome.gas_used,
+            contract_address: outcome.contract_address,
+            return_data: outcome.return_data,
+            logs: outcome.logs,
+            revert_data: outcome.revert_data,
+            status: outcome.status,
+            error: outcome.error,
         })
     }
 
     /// Execute a contract in read-only mode against the current state.
+    ///
+    /// The VM runs in static mode, so code that writes storage, emits events
+    /// or moves value fails with [`ExecutionStatus::StaticViolation`].
     pub fn query_contract(
         &self,
         contract: &Address,
@@ -278,24 +446,28 @@ impl<'a> Executor<'a> {
                 success: false,
                 gas_used: 0,
                 return_data: Vec::new(),
+                logs: Vec::new(),
+                revert_data: Vec::new(),
+                status: None,
                 error: Some("contract not found or no code".to_string()),
             });
         }
 
-        let code = self
-            .state
-            .get_code_for_address(contract)?
+        let program = load_program(self.state, &self.programs, contract)?
             .ok_or_else(|| ExecutionError::VmError("missing contract bytecode".to_string()))?;
 
         let execution = self.execute_contract_code(
-            &code,
+            program,
             contract,
             query.caller,
             query.call_value,
             query.gas_limit,
             query.data,
-            query.block_number,
-            query.timestamp,
+            &query.block,
+            &TxContext {
+                origin: query.caller,
+                gas_price: 0,
+            },
             true,
         )?;
 
@@ -303,16 +475,16 @@ impl<'a> Executor<'a> {
             success: execution.success,
             gas_used: execution.gas_used,
             return_data: execution.return_data,
+            logs: execution.logs,
+            revert_data: execution.revert_data,
+            status: Some(execution.status),
             error: execution.error,
         })
     }
 
     /// Execute transfer where sender balance is already deducted (just add to recipient).
     /// This is used when executor deducts max_cost (value + gas) upfront.
-    fn execute_transfer_without_sender_deduction(
-        &self,
-        tx: &Transaction,
-    ) -> Result<(bool, u64, Option<Address>, Option<String>)> {
+    fn execute_transfer_without_sender_deduction(&self, tx: &Transaction) -> Result<TxOutcome> {
         let to = tx.to.expect("transfer must have recipient");
 
         // Just add value to recipient (sender already deducted in max_cost)
@@ -321,16 +493,15 @@ impl<'a> Executor<'a> {
         }
 
         // Transfer uses 21,000 gas
-        Ok((true, 21_000, None, None))
+        Ok(TxOutcome {
+            success: true,
+            gas_used: 21_000,
+            ..TxOutcome::default()
+        })
     }
 
     /// Execute a contract deployment transaction.
-    fn execute_deploy(
-        &self,
-        tx: &Transaction,
-        block_number: u64,
-        timestamp: u64,
-    ) -> Result<(bool, u64, Option<Address>, Option<String>)> {
+    fn execute_deploy(&self, tx: &Transaction, block: &BlockContext) -> Result<TxOutcome> {
         // Calculate contract address and unpack runtime/init payload
         let contract_addr = tx
             .contract_address()
@@ -338,61 +509,84 @@ impl<'a> Executor<'a> {
         let (runtime_code, init_data) = decode_deployment_payload(&tx.data)?;
 
         // Deployment gas is the base create cost plus per-byte code cost.
-        let base_gas = 32_000 + (runtime_code.len() as u64 * 200);
+        let schedule = self.gas_schedules.at(block.number);
+        let base_gas = schedule
+            .create
+            .saturating_add(runtime_code.len() as u64 * schedule.create_per_byte);
         if tx.gas_limit < base_gas {
-            return Ok((
-                false,
-                tx.gas_limit,
-                None,
-                Some(
+            return Ok(TxOutcome {
+                status: Some(ExecutionStatus::OutOfGas),
+                ..TxOutcome::failed(
+                    tx.gas_limit,
                     VmError::OutOfGas {
                         required: base_gas,
                         remaining: tx.gas_limit,
                     }
                     .to_string(),
-                ),
-            ));
+                )
+            });
         }
 
+        // Reject code that would fail to decode before storing it.
+        let program = match verify(&runtime_code) {
+            Ok(program) => program,
+            Err(err) => {
+                return Ok(TxOutcome::failed(
+                    base_gas,
+                    format!("invalid contract bytecode: {err}"),
+                ));
+            }
+        };
+
         // Store bytecode by hash so later calls can load it by account code_hash.
         let code_hash = minichain_core::hash(&runtime_code);
         self.state.put_code(&code_hash, &runtime_code)?;
+        let program = self.programs.insert_program(code_hash, program);
 
         // Run optional init calldata against the freshly created contract storage.
         let mut gas_used = base_gas;
+        let mut logs = Vec::new();
         if !init_data.is_empty() {
             let init_execution = self.execute_contract_code(
-                &runtime_code,
+                program,
                 &contract_addr,
                 tx.from,
                 0,
                 tx.gas_limit.saturating_sub(base_gas),
                 &init_data,
-                block_number,
-                timestamp,
+                block,
+                &tx_context(tx),
                 false,
             )?;
             gas_used = gas_used.saturating_add(init_execution.gas_used);
             if !init_execution.success {
-                return Ok((false, gas_used, None, init_execution.error));
+                return Ok(TxOutcome {
+                    gas_used,
+                    ..init_execution.into()
+                });
             }
+            logs = init_execution.logs;
         }
 
-        // Only persist the account if the init execution succeeded.
-        let mut contract_account = Account::new_contract(code_hash);
-        contract_account.balance = tx.value;
+        // Only persist the account if the init execution succeeded. Init may
+        // already have changed it (e.g. CREATE bumps its nonce), so build on
+        // the stored account rather than replacing it.
+        let mut contract_account = self.state.get_account(&contract_addr)?;
+        contract_account.code_hash = Some(code_hash);
+        contract_account.balance = contract_account.balance.saturating_add(tx.value);
         self.state.put_account(&contract_addr, &contract_account)?;
 
-        Ok((true, gas_used, Some(contract_addr), None))
+        Ok(TxOutcome {
+            success: true,
+            gas_used,
+            contract_address: Some(contract_addr),
+            logs,
+            ..TxOutcome::default()
+        })
     }
 
     /// Execute a contract call transaction.
-    fn execute_call(
-        &self,
-        tx: &Transaction,
-        block_number: u64,
-        timestamp: u64,
-    ) -> Result<(bool, u64, Option<Address>, Option<String>)> {
+    fn execute_call(&self, tx: &Transaction, block: &BlockContext) -> Result<TxOutcome> {
         let contract_addr = tx.to.expect("call must have recipient");
 
         // Get contract account
@@ -400,100 +594,154 @@ impl<'a> Executor<'a> {
 
         // Check if contract exists
         if !contract.is_contract() {
-            return Ok((
-                false,
+            return Ok(TxOutcome::failed(
                 21_000,
-                None,
-                Some("contract not found or no code".to_string()),
+                "contract not found or no code".to_string(),
             ));
         }
 
         // Load runtime bytecode and execute against a transactional storage overlay.
-        let code = self
-            .state
-            .get_code_for_address(&contract_addr)?
+        let program = load_program(self.state, &self.programs, &contract_addr)?
             .ok_or_else(|| ExecutionError::VmError("missing contract bytecode".to_string()))?;
 
+        // Call value is credited inside the pending state, so it is dropped on revert.
         let execution = self.execute_contract_code(
-            &code,
+            program,
             &contract_addr,
             tx.from,
             tx.value,
             tx.gas_limit,
             &tx.data,
-            block_number,
-            timestamp,
+            block,
+            &tx_context(tx),
             false,
         )?;
 
-        // Only credit transferred value to the contract if execution did not revert.
-        if execution.success && tx.value > 0 {
-            self.state.add_balance(&contract_addr, tx.value)?;
-        }
-
-        Ok((execution.success, execution.gas_used, None, execution.error))
+        Ok(execution.into())
     }
 
     #[allow(clippy::too_many_arguments)]
     fn execute_contract_code(
         &self,
-        code: &[u8],
+        program: Arc<Program>,
         contract_addr: &Address,
         caller: Address,
         call_value: u64,
         gas_limit: u64,
         calldata: &[u8],
-        block_number: u64,
-        timestamp: u64,
+        block: &BlockContext,
+        tx: &TxContext,
         read_only: bool,
     ) -> Result<VmExecution> {
-        // Buffer SSTORE writes in memory first so reverts and queries do not mutate state.
-        let overlay = OverlayStorage::new(self.state, *contract_addr);
-        let writes = overlay.writes.clone();
+        // Buffer state changes first so reverts and queries do not mutate state.
+        let pending = Rc::new(RefCell::new(PendingState::default()));
+        let host = Rc::new(ChainHost::new(
+            self.state,
+            pending.clone(),
+            self.programs.clone(),
+            self.precompiles.clone(),
+        ));
+        if call_value > 0 {
+            host.credit(contract_addr, call_value);
+        }
 
-        let mut vm = Vm::new_with_context(
-            code.to_vec(),
+        let schedule = self.gas_schedules.at(block.number);
+        let mut vm = Vm::from_program(
+            program,
             gas_limit,
             caller,
             *contract_addr,
             call_value,
-            block_number,
-            timestamp,
+            schedule.clone(),
         );
-        vm.load_memory(0, calldata)
-            .map_err(|err| ExecutionError::VmError(err.to_string()))?;
-        vm.set_storage(Box::new(overlay));
+        vm.set_block_context(block.clone());
+        vm.set_tx_context(tx.clone());
+        vm.set_calldata(calldata.to_vec());
+        vm.set_storage(Box::new(OverlayStorage::new(
+            self.state,
+            *contract_addr,
+            pending.clone(),
+        )));
+        vm.set_host_functions(Rc::new(ChainSyscalls::new(
+            host.clone(),
+            block.clone(),
+            schedule,
+        )));
+        vm.set_host(host);
+        vm.set_max_return_size(self.max_return_size);
+        vm.set_static(read_only);
+        if let Some(inspector) = &self.inspector {
+            vm.set_inspector(inspector.clone());
+        }
 
         match vm.run() {
             Ok(result) => {
-                // Commit storage changes only for successful state-changing executions.
-                if !read_only {
-                    self.commit_storage_writes(contract_addr, &writes.borrow())?;
+                // Commit state changes only for successful state-changing executions.
+                if !read_only && result.success {
+                    self.commit_pending_state(&pending.borrow())?;
                 }
+                // Events only survive a successful run, like state changes.
+                let logs = if result.success {
+                    result.events
+                } else {
+                    Vec::new()
+                };
+                let error = (result.status == ExecutionStatus::FellOffEnd)
+                    .then(|| "execution ran past the end of the code".to_string());
                 Ok(VmExecution {
                     success: result.success,
+                    status: result.status,
                     gas_used: result.gas_used,
                     return_data: result.return_data,
-                    error: None,
+                    logs,
+                    revert_data: Vec::new(),
+                    error,
+                })
+            }
+            Err(err) => {
+                let error = match &err {
+                    VmError::Reverted(data) => match decode_revert_reason(data) {
+                        Some(reason) => format!("{err}: {reason}"),
+                        None => err.to_string(),
+                    },
+                    _ => err.to_string(),
+                };
+                let status = ExecutionStatus::from(err);
+                let revert_data = match &status {
+                    ExecutionStatus::Reverted(data) => data.clone(),
+                    _ => Vec::new(),
+                };
+                Ok(VmExecution {
+                    success: false,
+                    status,
+                    gas_used: gas_limit.saturating_sub(vm.gas_remaining()),
+                    return_data: Vec::new(),
+                    logs: Vec::new(),
+                    revert_data,
+                    error: Some(error),
                 })
             }
-            Err(err) => Ok(VmExecution {
-                success: false,
-                gas_used: gas_limit.saturating_sub(vm.gas_remaining()),
-                return_data: Vec::new(),
-                error: Some(err.to_string()),
-            }),
         }
     }
 
-    fn commit_storage_writes(
-        &self,
-        contract_addr: &Address,
-        writes: &HashMap<[u8; 32], [u8; 32]>,
-    ) -> Result<()> {
+    fn commit_pending_state(&self, pending: &PendingState) -> Result<()> {
+        // Create contract accounts first so the nonce and balance updates
+        // below land on them instead of being overwritten.
+        for (address, code) in &pending.deployments {
+            let balance = self.state.get_balance(address)?;
+            self.state.deploy_contract(address, code, balance)?;
+        }
+        for (address, nonce) in &pending.nonces {
+            let mut account = self.state.get_account(address)?;
+            account.nonce = *nonce;
+            self.state.put_account(address, &account)?;
+        }
         // Apply each buffered slot update to persistent contract storage.
-        for (slot, value) in writes {
-            self.state.sstore(contract_addr, slot, value)?;
+        for ((contract, slot), value) in &pending.storage {
+            self.state.sstore(contract, slot, value)?;
+        }
+        for (address, balance) in &pending.balances {
+            self.state.set_balance(address, *balance)?;
         }
         Ok(())
     }
@@ -503,13 +751,10 @@ impl<'a> Executor<'a> {
         let block_hash = block.hash();
         let mut receipts = Vec::new();
         let mut total_gas_used = 0;
+        let context = self.block_context(&block.header)?;
 
         for tx in &block.transactions {
-            let receipt = self.execute_transaction_with_context(
-                tx,
-                block.header.height,
-                block.header.timestamp,
-            )?;
+            let receipt = self.execute_transaction_with_context(tx, &context)?;
             total_gas_used += receipt.gas_used;
             receipts.push(receipt);
         }
@@ -528,9 +773,11 @@ impl<'a> Executor<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use minichain_assembler::assemble;
-    use minichain_core::Keypair;
+    use crate::precompiles::{BLAKE3_ADDRESS, ED25519_VERIFY_ADDRESS};
+    use minichain_assembler::{assemble, assemble_with_labels};
+    use minichain_core::{Account, Keypair};
     use minichain_storage::Storage;
+    use minichain_vm::{GasCosts, GasProfiler, GasSchedule, Opcode};
 
     fn setup() -> (Storage, Keypair) {
         (Storage::open_temporary().unwrap(), Keypair::generate())
@@ -542,7 +789,7 @@ mod tests {
             .entry main
             main:
                 LOADI R0, 0
-                LOAD64 R1, R0
+                CALLDATALOAD R1, R0
                 LOADI R2, 0
                 EQ R3, R1, R2
                 LOADI R4, get_value
@@ -564,7 +811,7 @@ mod tests {
 
             set_value:
                 LOADI R5, 8
-                LOAD64 R6, R5
+                CALLDATALOAD R6, R5
                 LOADI R7, 1
                 SSTORE R7, R6
                 HALT
@@ -636,6 +883,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_deployment_rejects_invalid_bytecode() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+
+        state.set_balance(&from, 1_000_000).unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        // LOADI cut off after its register byte.
+        let payload = encode_deployment_payload(&[0x00, 0x70, 0x00], &[]);
+        let tx = Transaction::deploy(from, payload, 0, 200_000, 1).signed(&keypair);
+        let contract_addr = tx.contract_address().unwrap();
+        let receipt = Executor::new(&state).execute_transaction(&tx).unwrap();
+
+        assert!(!receipt.success);
+        assert_eq!(
+            receipt.error.as_deref(),
+            Some(
+                "invalid contract bytecode: Truncated LOADI at offset 1: needs 10 bytes, 2 available"
+            )
+        );
+        assert!(!state.get_account(&contract_addr).unwrap().is_contract());
+    }
+
     #[test]
     fn test_execute_call_updates_storage() {
         let (storage, keypair) = setup();
@@ -670,8 +944,11 @@ mod tests {
                     data: &0u64.to_le_bytes(),
                     call_value: 0,
                     gas_limit: 100_000,
-                    block_number: 1,
-                    timestamp: 1,
+                    block: BlockContext {
+                        number: 1,
+                        timestamp: 1,
+                        ..BlockContext::default()
+                    },
                 },
             )
             .unwrap();
@@ -684,7 +961,54 @@ mod tests {
     }
 
     #[test]
-    fn test_query_does_not_commit_storage_writes() {
+    fn test_gas_schedule_follows_block_height() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let payload = encode_deployment_payload(&sample_contract(), &[]);
+
+        state.set_balance(&from, 1_000_000).unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        let deploy = Transaction::deploy(from, payload, 0, 200_000, 1).signed(&keypair);
+        let receipt = Executor::new(&state).execute_transaction(&deploy).unwrap();
+        let contract_addr = receipt.contract_address.unwrap();
+
+        let repriced = GasSchedule {
+            sload: 5_000,
+            ..GasSchedule::default()
+        };
+        let executor = Executor::new(&state)
+            .with_gas_schedules(GasSchedules::default().with_activation(10, repriced));
+
+        let calldata = 0u64.to_le_bytes();
+        let gas_at = |block_number| {
+            executor
+                .query_contract(
+                    &contract_addr,
+                    ContractQuery {
+                        caller: from,
+                        data: &calldata,
+                        call_value: 0,
+                        gas_limit: 100_000,
+                        block: BlockContext {
+                            number: block_number,
+                            timestamp: 1,
+                            ..BlockContext::default()
+                        },
+                    },
+                )
+                .unwrap()
+                .gas_used
+        };
+
+        assert_eq!(gas_at(10) - gas_at(9), 5_000 - GasCosts::SLOAD);
+    }
+
+    #[test]
+    fn test_query_rejects_storage_writes() {
         let (storage, keypair) = setup();
         let state = StateManager::new(&storage);
         let from = keypair.address();
@@ -710,12 +1034,16 @@ mod tests {
                     data: &calldata,
                     call_value: 0,
                     gas_limit: 100_000,
-                    block_number: 1,
-                    timestamp: 1,
+                    block: BlockContext {
+                        number: 1,
+                        timestamp: 1,
+                        ..BlockContext::default()
+                    },
                 },
             )
             .unwrap();
-        assert!(query.success);
+        assert!(!query.success);
+        assert_eq!(query.status, Some(ExecutionStatus::StaticViolation));
         calldata = 0u64.to_le_bytes().to_vec();
         let value = executor
             .query_contract(
@@ -725,8 +1053,11 @@ mod tests {
                     data: &calldata,
                     call_value: 0,
                     gas_limit: 100_000,
-                    block_number: 1,
-                    timestamp: 1,
+                    block: BlockContext {
+                        number: 1,
+                        timestamp: 1,
+                        ..BlockContext::default()
+                    },
                 },
             )
             .unwrap();
@@ -735,4 +1066,1033 @@ mod tests {
             0
         );
     }
+
+    #[test]
+    fn test_return_data_in_receipt_and_query() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let contract = Address::from_bytes([0xD1; 20]);
+
+        // Returns two words: the first calldata word and a constant.
+        let code = assemble(
+            r#"
+                LOADI R0, 64
+                LOADI R1, 0
+                CALLDATALOAD R2, R1
+                STORE64 R0, R2
+                LOADI R3, 72
+                LOADI R4, 7
+                STORE64 R3, R4
+                LOADI R5, 16
+                RETURN R0, R5
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&contract, &code, 0).unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        let mut expected = 42u64.to_le_bytes().to_vec();
+        expected.extend_from_slice(&7u64.to_le_bytes());
+
+        let executor = Executor::new(&state);
+        let tx = Transaction::call(
+            from,
+            contract,
+            42u64.to_le_bytes().to_vec(),
+            0,
+            0,
+            100_000,
+            1,
+        )
+        .signed(&keypair);
+        let receipt = executor.execute_transaction(&tx).unwrap();
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(receipt.status, Some(ExecutionStatus::Returned));
+        assert_eq!(receipt.return_data, expected);
+
+        let query = ContractQuery {
+            caller: from,
+            data: &42u64.to_le_bytes(),
+            call_value: 0,
+            gas_limit: 100_000,
+            block: BlockContext {
+                number: 1,
+                timestamp: 1,
+                ..BlockContext::default()
+            },
+        };
+        let result = executor.query_contract(&contract, query).unwrap();
+        assert!(result.success);
+        assert_eq!(result.return_data, expected);
+
+        let limited = Executor::new(&state).with_max_return_size(8);
+        let query = ContractQuery {
+            caller: from,
+            data: &42u64.to_le_bytes(),
+            call_value: 0,
+            gas_limit: 100_000,
+            block: BlockContext {
+                number: 1,
+                timestamp: 1,
+                ..BlockContext::default()
+            },
+        };
+        let result = limited.query_contract(&contract, query).unwrap();
+        assert!(!result.success);
+        assert!(result.return_data.is_empty());
+    }
+
+    #[test]
+    fn test_revert_reason_in_receipt_and_query() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let contract = Address::from_bytes([0xD2; 20]);
+
+        // Reverts with the four bytes "nope".
+        let code = assemble(
+            r#"
+                LOADI R0, 0
+                LOADI R1, 0x65706F6E
+                STORE64 R0, R1
+                LOADI R2, 4
+                REVERT R0, R2
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&contract, &code, 0).unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        let executor = Executor::new(&state);
+        let tx = Transaction::call(from, contract, vec![0], 0, 0, 100_000, 1).signed(&keypair);
+        let receipt = executor.execute_transaction(&tx).unwrap();
+        assert!(!receipt.success);
+        assert_eq!(receipt.revert_data, b"nope");
+        assert_eq!(
+            receipt.status,
+            Some(ExecutionStatus::Reverted(b"nope".to_vec()))
+        );
+        assert_eq!(receipt.revert_reason(), Some("nope"));
+        assert_eq!(receipt.error.as_deref(), Some("Execution reverted: nope"));
+
+        let query = ContractQuery {
+            caller: from,
+            data: &[0],
+            call_value: 0,
+            gas_limit: 100_000,
+            block: BlockContext {
+                number: 1,
+                timestamp: 1,
+                ..BlockContext::default()
+            },
+        };
+        let result = executor.query_contract(&contract, query).unwrap();
+        assert!(!result.success);
+        assert_eq!(result.revert_reason(), Some("nope"));
+    }
+
+    #[test]
+    fn test_execution_status_in_receipts() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let recipient = Address::from_bytes([0xD5; 20]);
+        let contract = Address::from_bytes([0xD6; 20]);
+
+        // Loops forever until it runs out of gas.
+        let code = assemble(
+            r#"
+            spin:
+                LOADI R0, spin
+                JUMP R0
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&contract, &code, 0).unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        let executor = Executor::new(&state);
+        let transfer = Transaction::transfer(from, recipient, 10, 0, 1).signed(&keypair);
+        let receipt = executor.execute_transaction(&transfer).unwrap();
+        assert!(receipt.success);
+        assert_eq!(receipt.status, None);
+
+        let tx = Transaction::call(from, contract, vec![0], 0, 1, 50_000, 1).signed(&keypair);
+        let receipt = executor.execute_transaction(&tx).unwrap();
+        assert!(!receipt.success);
+        assert_eq!(receipt.status, Some(ExecutionStatus::OutOfGas));
+        assert_eq!(receipt.gas_used, 50_000);
+    }
+
+    #[test]
+    fn test_receipt_records_events_only_on_success() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let contract = Address::from_bytes([0xE1; 20]);
+
+        // Emits Transfer(topic 0x7472, amount) and reverts when calldata is non-zero.
+        let code = assemble(
+            r#"
+                LOADI R0, 0
+                CALLDATALOAD R1, R0
+                LOADI R2, 8
+                LOADI R3, 0x7472
+                LOADI R4, 500
+                STORE64 R0, R4
+                LOG1 R0, R2, R3
+                LOADI R5, fail
+                JUMPI R1, R5
+                HALT
+            fail:
+                REVERT
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&contract, &code, 0).unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        let executor = Executor::new(&state);
+        let tx = Transaction::call(
+            from,
+            contract,
+            0u64.to_le_bytes().to_vec(),
+            0,
+            0,
+            100_000,
+            1,
+        )
+        .signed(&keypair);
+        let receipt = executor.execute_transaction(&tx).unwrap();
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(
+            receipt.logs,
+            vec![Log::new(
+                contract,
+                vec![0x7472],
+                500u64.to_le_bytes().to_vec()
+            )]
+        );
+
+        let tx = Transaction::call(
+            from,
+            contract,
+            1u64.to_le_bytes().to_vec(),
+            0,
+            1,
+            100_000,
+            1,
+        )
+        .signed(&keypair);
+        let receipt = executor.execute_transaction(&tx).unwrap();
+        assert!(!receipt.success);
+        assert!(receipt.logs.is_empty());
+    }
+
+    #[test]
+    fn test_full_word_storage_roundtrip() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let contract = Address::from_bytes([0xF1; 20]);
+
+        // Calldata is key (32 bytes) followed by value (32 bytes).
+        let code = assemble(
+            r#"
+                LOADI R0, 0
+                LOADI R1, 32
+                CALLDATASIZE R3
+                CALLDATACOPY R0, R0, R3
+                SSTOREM R0, R1
+                LOADI R2, 64
+                SLOADM R2, R0
+                RETURN R2, R1
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&contract, &code, 0).unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        let key: [u8; 32] = std::array::from_fn(|i| i as u8 + 1);
+        let value: [u8; 32] = std::array::from_fn(|i| 0xFF - i as u8);
+        let calldata = [key, value].concat();
+
+        let tx = Transaction::call(from, contract, calldata, 0, 0, 100_000, 1).signed(&keypair);
+        let receipt = Executor::new(&state).execute_transaction(&tx).unwrap();
+
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(receipt.return_data, value.to_vec());
+        assert_eq!(state.sload(&contract, &key).unwrap(), value);
+    }
+
+    /// Pays the amount at calldata offset 24 to the address at offset 0, then
+    /// returns the recipient's and its own balance. Reverts afterwards when
+    /// the amount is odd.
+    fn payout_contract() -> Vec<u8> {
+        assemble(
+            r#"
+                LOADI R0, 0
+                LOADI R1, 24
+                CALLDATACOPY R0, R0, R1
+                CALLDATALOAD R2, R1
+                TRANSFER R0, R2
+                LOADI R3, 32
+                BALANCE R4, R0
+                SELFBALANCE R5
+                STORE64 R3, R4
+                LOADI R6, 40
+                STORE64 R6, R5
+                LOADI R7, 1
+                AND R8, R2, R7
+                LOADI R9, fail
+                JUMPI R8, R9
+                LOADI R7, 16
+                RETURN R3, R7
+            fail:
+                REVERT
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn payout_calldata(recipient: Address, amount: u64) -> Vec<u8> {
+        let mut calldata = recipient.0.to_vec();
+        calldata.resize(24, 0);
+        calldata.extend_from_slice(&amount.to_le_bytes());
+        calldata
+    }
+
+    #[test]
+    fn test_contract_transfer_pays_out_call_value() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let contract = Address::from_bytes([0xA1; 20]);
+        let recipient = Address::from_bytes([0xA2; 20]);
+
+        state
+            .deploy_contract(&contract, &payout_contract(), 0)
+            .unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        let calldata = payout_calldata(recipient, 300);
+        let tx = Transaction::call(from, contract, calldata, 1000, 0, 100_000, 1).signed(&keypair);
+        let receipt = Executor::new(&state).execute_transaction(&tx).unwrap();
+
+        assert!(receipt.success, "{:?}", receipt.error);
+        let mut expected = 300u64.to_le_bytes().to_vec();
+        expected.extend_from_slice(&700u64.to_le_bytes());
+        assert_eq!(receipt.return_data, expected);
+        assert_eq!(state.get_balance(&recipient).unwrap(), 300);
+        assert_eq!(state.get_balance(&contract).unwrap(), 700);
+    }
+
+    #[test]
+    fn test_contract_transfer_rolled_back_on_revert() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let contract = Address::from_bytes([0xA1; 20]);
+        let recipient = Address::from_bytes([0xA2; 20]);
+
+        state
+            .deploy_contract(&contract, &payout_contract(), 500)
+            .unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+        let executor = Executor::new(&state);
+
+        // Odd amount: the transfer happens, then the frame reverts.
+        let tx = Transaction::call(
+            from,
+            contract,
+            payout_calldata(recipient, 301),
+            0,
+            0,
+            100_000,
+            1,
+        )
+        .signed(&keypair);
+        let receipt = executor.execute_transaction(&tx).unwrap();
+        assert!(!receipt.success);
+        assert_eq!(state.get_balance(&recipient).unwrap(), 0);
+        assert_eq!(state.get_balance(&contract).unwrap(), 500);
+
+        // More than the contract holds.
+        let tx = Transaction::call(
+            from,
+            contract,
+            payout_calldata(recipient, 600),
+            0,
+            1,
+            100_000,
+            1,
+        )
+        .signed(&keypair);
+        let receipt = executor.execute_transaction(&tx).unwrap();
+        assert!(!receipt.success);
+        assert_eq!(
+            receipt.error,
+            Some(VmError::TransferFailed { amount: 600 }.to_string())
+        );
+        assert_eq!(state.get_balance(&contract).unwrap(), 500);
+    }
+
+    fn slot(key: u64) -> [u8; 32] {
+        let mut slot = [0u8; 32];
+        slot[24..32].copy_from_slice(&key.to_be_bytes());
+        slot
+    }
+
+    fn slot_value(state: &StateManager, contract: &Address, key: u64) -> u64 {
+        let value = state.sload(contract, &slot(key)).unwrap();
+        u64::from_be_bytes(value[24..32].try_into().unwrap())
+    }
+
+    /// Calls the contract whose address is passed as calldata and records
+    /// the success flag in slot 3 and the first returned word in slot 2.
+    fn caller_contract() -> Vec<u8> {
+        assemble(
+            r#"
+                LOADI R1, 0
+                LOADI R8, 20
+                CALLDATACOPY R1, R1, R8
+                LOADI R2, 0
+                LOADI R3, 50000
+                LOADI R4, 0
+                LOADI R5, 0
+                LOADI R6, 32
+                LOADI R7, 8
+                CALL R0, R1, R2, R3, R4, R5, R6, R7
+                LOADI R8, 32
+                LOAD64 R9, R8
+                LOADI R10, 2
+                SSTORE R10, R9
+                LOADI R10, 3
+                SSTORE R10, R0
+                HALT
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn call_with_address(
+        state: &StateManager,
+        keypair: &Keypair,
+        caller: Address,
+        callee: Address,
+    ) -> TransactionReceipt {
+        let from = keypair.address();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+        let tx =
+            Transaction::call(from, caller, callee.0.to_vec(), 0, 0, 200_000, 1).signed(keypair);
+        Executor::new(state).execute_transaction(&tx).unwrap()
+    }
+
+    #[test]
+    fn test_call_runs_callee_in_child_frame() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let caller = Address::from_bytes([0xC1; 20]);
+        let callee = Address::from_bytes([0xC2; 20]);
+
+        state
+            .deploy_contract(&caller, &caller_contract(), 0)
+            .unwrap();
+        let callee_code = assemble(
+            r#"
+                LOADI R0, 1
+                LOADI R1, 7
+                SSTORE R0, R1
+                LOADI R2, 0
+                LOADI R3, 99
+                STORE64 R2, R3
+                HALT
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&callee, &callee_code, 0).unwrap();
+
+        let receipt = call_with_address(&state, &keypair, caller, callee);
+
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(slot_value(&state, &caller, 3), 1);
+        assert_eq!(slot_value(&state, &caller, 2), 99);
+        assert_eq!(slot_value(&state, &callee, 1), 7);
+    }
+
+    #[test]
+    fn test_call_runs_precompile() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let caller = Address::from_bytes([0xC1; 20]);
+        state
+            .deploy_contract(&caller, &caller_contract(), 0)
+            .unwrap();
+
+        let receipt = call_with_address(&state, &keypair, caller, BLAKE3_ADDRESS);
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(slot_value(&state, &caller, 3), 1);
+        let digest = minichain_core::hash(&[]);
+        assert_eq!(
+            slot_value(&state, &caller, 2),
+            u64::from_le_bytes(digest.0[..8].try_into().unwrap())
+        );
+
+        // Empty input is not a valid signature check, so the call fails.
+        let receipt = call_with_address(&state, &keypair, caller, ED25519_VERIFY_ADDRESS);
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(slot_value(&state, &caller, 3), 0);
+    }
+
+    #[test]
+    fn test_call_input_is_zero_padded_and_bounded() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let callee = Address::from_bytes([0xC2; 20]);
+        // Returns its calldata size.
+        let callee_code = assemble(
+            r#"
+                CALLDATASIZE R0
+                LOADI R1, 0
+                STORE64 R1, R0
+                LOADI R2, 8
+                RETURN R1, R2
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&callee, &callee_code, 0).unwrap();
+
+        // Sends `in_len` bytes from offset 0, though only 20 are allocated,
+        // and stores the callee's answer in slot 2.
+        let caller_with_input = |in_len: u64| {
+            assemble(&format!(
+                r#"
+                    LOADI R1, 0
+                    LOADI R8, 20
+                    CALLDATACOPY R1, R1, R8
+                    LOADI R3, 50000
+                    LOADI R5, {in_len}
+                    LOADI R6, 32
+                    LOADI R7, 8
+                    CALL R0, R1, R2, R3, R1, R5, R6, R7
+                    LOAD64 R9, R6
+                    LOADI R10, 2
+                    SSTORE R10, R9
+                    HALT
+                "#
+            ))
+            .unwrap()
+        };
+
+        let caller = Address::from_bytes([0xC1; 20]);
+        state
+            .deploy_contract(&caller, &caller_with_input(64), 0)
+            .unwrap();
+        let receipt = call_with_address(&state, &keypair, caller, callee);
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(slot_value(&state, &caller, 2), 64);
+
+        // A length that does not fit in memory fails instead of wrapping to 0.
+        let caller = Address::from_bytes([0xC4; 20]);
+        state
+            .deploy_contract(&caller, &caller_with_input(1 << 32), 0)
+            .unwrap();
+        let receipt = call_with_address(&state, &keypair, caller, callee);
+        assert!(!receipt.success);
+    }
+
+    #[test]
+    fn test_reverted_call_rolls_back_callee_writes() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let caller = Address::from_bytes([0xC1; 20]);
+        let callee = Address::from_bytes([0xC3; 20]);
+
+        state
+            .deploy_contract(&caller, &caller_contract(), 0)
+            .unwrap();
+        let callee_code = assemble(
+            r#"
+                LOADI R0, 1
+                LOADI R1, 7
+                SSTORE R0, R1
+                REVERT
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&callee, &callee_code, 0).unwrap();
+
+        let receipt = call_with_address(&state, &keypair, caller, callee);
+
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(slot_value(&state, &caller, 3), 0);
+        assert_eq!(slot_value(&state, &callee, 1), 0);
+    }
+
+    #[test]
+    fn test_gas_profiler_charges_callee_labels() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let caller = Address::from_bytes([0xC1; 20]);
+        let callee = Address::from_bytes([0xC2; 20]);
+
+        state
+            .deploy_contract(&caller, &caller_contract(), 0)
+            .unwrap();
+        let (callee_code, labels) = assemble_with_labels(
+            r#"
+            main:
+                LOADI R0, 1
+            store:
+                LOADI R1, 7
+                SSTORE R0, R1
+                HALT
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&callee, &callee_code, 0).unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        let profiler = Rc::new(RefCell::new(GasProfiler::new().with_labels(callee, labels)));
+        let tx =
+            Transaction::call(from, caller, callee.0.to_vec(), 0, 0, 200_000, 1).signed(&keypair);
+        let receipt = Executor::new(&state)
+            .with_inspector(profiler.clone())
+            .execute_transaction(&tx)
+            .unwrap();
+        assert!(receipt.success, "{:?}", receipt.error);
+
+        let profiler = profiler.borrow();
+        assert_eq!(profiler.total().gas, receipt.gas_used);
+
+        let by_label = profiler.by_label();
+        let store = by_label.iter().find(|(label, _)| label == "store").unwrap();
+        assert_eq!(store.1.count, 3);
+        assert!(store.1.gas >= GasCosts::SSTORE_SET);
+
+        // The CALL itself is charged only its own cost, not the callee's gas.
+        let call = profiler
+            .by_opcode()
+            .into_iter()
+            .find(|(opcode, _)| *opcode == Opcode::CALL)
+            .unwrap();
+        assert!(call.1.gas < GasCosts::SSTORE_SET);
+
+        let folded = profiler.folded();
+        assert!(folded.contains(&format!("{};store;SSTORE ", caller.to_hex())));
+    }
+
+    #[test]
+    fn test_gas_profiler_counts_failing_step() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        let contract = Address::from_bytes([0xD7; 20]);
+
+        // HASH pays its fixed cost, then runs out of gas on the per-word cost.
+        let code = assemble(
+            r#"
+                LOADI R0, 0
+                LOADI R1, 100000
+                HASH R0, R0, R1
+                HALT
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&contract, &code, 0).unwrap();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+
+        let profiler = Rc::new(RefCell::new(GasProfiler::new()));
+        let tx = Transaction::call(from, contract, vec![0], 0, 0, 10_000, 1).signed(&keypair);
+        let receipt = Executor::new(&state)
+            .with_inspector(profiler.clone())
+            .execute_transaction(&tx)
+            .unwrap();
+        assert_eq!(receipt.status, Some(ExecutionStatus::OutOfGas));
+
+        let profiler = profiler.borrow();
+        assert_eq!(profiler.total().gas, receipt.gas_used);
+        let hash = profiler
+            .by_opcode()
+            .into_iter()
+            .find(|(opcode, _)| *opcode == Opcode::HASH)
+            .unwrap();
+        assert_eq!(hash.1.gas, GasCosts::HASH);
+    }
+
+    #[test]
+    fn test_program_cache_shared_across_calls() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let caller = Address::from_bytes([0xC1; 20]);
+        let callee = Address::from_bytes([0xC2; 20]);
+        let other_callee = Address::from_bytes([0xC3; 20]);
+
+        state
+            .deploy_contract(&caller, &caller_contract(), 0)
+            .unwrap();
+        let callee_code = assemble("LOADI R0, 1\nHALT").unwrap();
+        state.deploy_contract(&callee, &callee_code, 0).unwrap();
+        state
+            .deploy_contract(&other_callee, &callee_code, 0)
+            .unwrap();
+
+        let programs = Arc::new(ProgramCache::new());
+        let from = keypair.address();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+        for (nonce, target) in [callee, other_callee].into_iter().enumerate() {
+            let tx =
+                Transaction::call(from, caller, target.0.to_vec(), 0, nonce as u64, 200_000, 1)
+                    .signed(&keypair);
+            let receipt = Executor::new(&state)
+                .with_program_cache(programs.clone())
+                .execute_transaction(&tx)
+                .unwrap();
+            assert!(receipt.success, "{:?}", receipt.error);
+        }
+
+        // Both callees share one decoded program, alongside the caller's.
+        assert_eq!(programs.len(), 2);
+        let code_hash = state.get_account(&callee).unwrap().code_hash.unwrap();
+        assert_eq!(programs.get(&code_hash).unwrap().code(), &callee_code[..]);
+    }
+
+    /// Deploys its calldata with `endowment`, then calls the new contract.
+    fn factory_contract(endowment: u64) -> Vec<u8> {
+        assemble(&format!(
+            r#"
+                CALLDATASIZE R3
+                LOADI R1, 512
+                LOADI R2, 0
+                CALLDATACOPY R2, R2, R3
+                LOADI R4, {endowment}
+                CREATE R5, R1, R2, R3, R4
+                LOADI R6, 50000
+                CALL R7, R1, R2, R6, R2, R2, R2, R2
+                HALT
+            "#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_create_deploys_contract_from_contract() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let factory = Address::from_bytes([0xF1; 20]);
+        state
+            .deploy_contract(&factory, &factory_contract(100), 1_000)
+            .unwrap();
+        let child_code = assemble(
+            r#"
+                LOADI R0, 1
+                LOADI R1, 7
+                SSTORE R0, R1
+                HALT
+            "#,
+        )
+        .unwrap();
+
+        let from = keypair.address();
+        state
+            .put_account(&from, &Account::new_user(1_000_000))
+            .unwrap();
+        let tx =
+            Transaction::call(from, factory, child_code.clone(), 0, 0, 200_000, 1).signed(&keypair);
+        let receipt = Executor::new(&state).execute_transaction(&tx).unwrap();
+        assert!(receipt.success, "{:?}", receipt.error);
+
+        let child = minichain_core::contract_address(&factory, 0);
+        assert_eq!(
+            state.get_code_for_address(&child).unwrap(),
+            Some(child_code)
+        );
+        assert_eq!(state.get_balance(&child).unwrap(), 100);
+        assert_eq!(state.get_balance(&factory).unwrap(), 900);
+        assert_eq!(state.get_nonce(&factory).unwrap(), 1);
+        assert_eq!(slot_value(&state, &child, 1), 7);
+    }
+
+    #[test]
+    fn test_failed_create_rolls_back() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let factory = Address::from_bytes([0xF2; 20]);
+        state
+            .deploy_contract(&factory, &factory_contract(5_000), 1_000)
+            .unwrap();
+        state
+            .put_account(&keypair.address(), &Account::new_user(1_000_000))
+            .unwrap();
+
+        // The endowment exceeds the factory's balance, so CREATE yields 0
+        // and the factory carries on.
+        let tx = Transaction::call(keypair.address(), factory, vec![0x00], 0, 0, 200_000, 1)
+            .signed(&keypair);
+        let receipt = Executor::new(&state).execute_transaction(&tx).unwrap();
+        assert!(receipt.success, "{:?}", receipt.error);
+
+        let child = minichain_core::contract_address(&factory, 0);
+        assert_eq!(state.get_code_for_address(&child).unwrap(), None);
+        assert_eq!(state.get_balance(&factory).unwrap(), 1_000);
+        assert_eq!(state.get_nonce(&factory).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_deploy_keeps_account_changes_made_by_init() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let from = keypair.address();
+        state
+            .put_account(&from, &Account::new_user(10_000_000))
+            .unwrap();
+        let child_code = assemble("HALT").unwrap();
+
+        // Init runs the factory with the child code as calldata, so it CREATEs once.
+        let payload = encode_deployment_payload(&factory_contract(0), &child_code);
+        let deploy = Transaction::deploy(from, payload, 0, 500_000, 1).signed(&keypair);
+        let factory = deploy.contract_address().unwrap();
+        let receipt = Executor::new(&state).execute_transaction(&deploy).unwrap();
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(state.get_nonce(&factory).unwrap(), 1);
+
+        // The next CREATE must use nonce 1 rather than collide with the first child.
+        let call =
+            Transaction::call(from, factory, child_code.clone(), 0, 1, 500_000, 1).signed(&keypair);
+        let receipt = Executor::new(&state).execute_transaction(&call).unwrap();
+        assert!(receipt.success, "{:?}", receipt.error);
+
+        for nonce in 0..2 {
+            let child = minichain_core::contract_address(&factory, nonce);
+            assert_eq!(
+                state.get_code_for_address(&child).unwrap(),
+                Some(child_code.clone())
+            );
+        }
+        assert_eq!(state.get_nonce(&factory).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_block_context_opcodes() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let chain = ChainStore::new(&storage);
+        let author = Address::from_bytes([0xAB; 20]);
+        let genesis = Block::genesis(author);
+        let parent = Block::new(1, genesis.hash(), vec![], Hash::ZERO, author);
+        chain.put_block(&genesis).unwrap();
+        chain.put_block(&parent).unwrap();
+
+        // Stores CHAINID, GASPRICE, the first 8 bytes of COINBASE and ORIGIN,
+        // and the first 8 bytes of the parent's BLOCKHASH in slots 1-5.
+        let contract = Address::from_bytes([0xB7; 20]);
+        let code = assemble(
+            r#"
+                LOADI R0, 1
+                CHAINID R1
+                SSTORE R0, R1
+                LOADI R0, 2
+                GASPRICE R1
+                SSTORE R0, R1
+                LOADI R2, 64
+                COINBASE R2
+                LOAD64 R1, R2
+                LOADI R0, 3
+                SSTORE R0, R1
+                ORIGIN R2
+                LOAD64 R1, R2
+                LOADI R0, 4
+                SSTORE R0, R1
+                LOADI R3, 1
+                BLOCKHASH R2, R3
+                LOAD64 R1, R2
+                LOADI R0, 5
+                SSTORE R0, R1
+                HALT
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&contract, &code, 0).unwrap();
+        state
+            .put_account(&keypair.address(), &Account::new_user(1_000_000))
+            .unwrap();
+
+        let tx = Transaction::call(keypair.address(), contract, vec![0x00], 0, 0, 200_000, 2)
+            .signed(&keypair);
+        let block = Block::new(2, parent.hash(), vec![tx], Hash::ZERO, author);
+        let result = Executor::new(&state)
+            .with_chain_id(9)
+            .with_chain_store(&chain)
+            .execute_block(&block)
+            .unwrap();
+        assert!(result.receipts[0].success, "{:?}", result.receipts[0].error);
+
+        let prefix = |bytes: &[u8]| u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        assert_eq!(slot_value(&state, &contract, 1), 9);
+        assert_eq!(slot_value(&state, &contract, 2), 2);
+        assert_eq!(slot_value(&state, &contract, 3), prefix(&author.0));
+        assert_eq!(
+            slot_value(&state, &contract, 4),
+            prefix(&keypair.address().0)
+        );
+        assert_eq!(slot_value(&state, &contract, 5), prefix(&parent.hash().0));
+    }
+
+    #[test]
+    fn test_transient_storage_lasts_one_transaction() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let contract = Address::from_bytes([0x7E; 20]);
+        state
+            .put_account(&keypair.address(), &Account::new_user(1_000_000))
+            .unwrap();
+
+        // A frame that finds transient slot 1 unset counts itself in slot 3,
+        // sets it to 5 and calls itself; the inner frame copies it to slot 2.
+        let code = assemble(
+            r#"
+                LOADI R0, 1
+                TLOAD R1, R0
+                LOADI R2, inner
+                JUMPI R1, R2
+                LOADI R9, 3
+                SLOAD R8, R9
+                ADDI R8, R8, 1
+                SSTORE R9, R8
+                LOADI R2, 5
+                TSTORE R0, R2
+                LOADI R3, 100
+                ADDRESSM R3
+                LOADI R4, 0
+                LOADI R5, 50000
+                CALL R10, R3, R4, R5, R4, R4, R4, R4
+                HALT
+            inner:
+                LOADI R9, 2
+                SSTORE R9, R1
+                HALT
+            "#,
+        )
+        .unwrap();
+        state.deploy_contract(&contract, &code, 0).unwrap();
+
+        for nonce in 0..2 {
+            let tx = Transaction::call(
+                keypair.address(),
+                contract,
+                vec![0x00],
+                0,
+                nonce,
+                200_000,
+                1,
+            )
+            .signed(&keypair);
+            let receipt = Executor::new(&state).execute_transaction(&tx).unwrap();
+            assert!(receipt.success, "{:?}", receipt.error);
+
+            // Each transaction starts with the slot unset again.
+            assert_eq!(slot_value(&state, &contract, 2), 5);
+            assert_eq!(slot_value(&state, &contract, 3), nonce + 1);
+        }
+    }
+
+    /// Returns the balance of the account passed as calldata.
+    fn syscall_balance_contract() -> Vec<u8> {
+        assemble(
+            r#"
+                LOADI R0, 0
+                LOADI R1, 20
+                CALLDATACOPY R0, R0, R1
+                LOADI R2, 1
+                SYSCALL R3, R2, R0, R1, R0
+                LOADI R4, 32
+                STORE64 R4, R3
+                LOADI R5, 8
+                RETURN R4, R5
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_syscall_balance() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let contract = Address::from_bytes([0x5C; 20]);
+        let account = Address::from_bytes([0xBA; 20]);
+        state
+            .put_account(&account, &Account::new_user(4_321))
+            .unwrap();
+        state
+            .deploy_contract(&contract, &syscall_balance_contract(), 0)
+            .unwrap();
+
+        let receipt = call_with_address(&state, &keypair, contract, account);
+        assert!(receipt.success, "{:?}", receipt.error);
+        assert_eq!(receipt.return_data, 4_321u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_syscall_gas_follows_schedule() {
+        let (storage, keypair) = setup();
+        let state = StateManager::new(&storage);
+        let contract = Address::from_bytes([0x5C; 20]);
+        state
+            .deploy_contract(&contract, &syscall_balance_contract(), 0)
+            .unwrap();
+
+        let repriced = GasSchedule {
+            balance: 5_000,
+            ..GasSchedule::default()
+        };
+        let executor = Executor::new(&state)
+            .with_gas_schedules(GasSchedules::default().with_activation(10, repriced));
+
+        let calldata = Address::from_bytes([0xBA; 20]).0;
+        let gas_at = |block_number| {
+            let result = executor
+                .query_contract(
+                    &contract,
+                    ContractQuery {
+                        caller: keypair.address(),
+                        data: &calldata,
+                        call_value: 0,
+                        gas_limit: 100_000,
+                        block: BlockContext {
+                            number: block_number,
+                            ..BlockContext::default()
+                        },
+                    },
+                )
+                .unwrap();
+            assert!(result.success, "{:?}", result.error);
+            result.gas_used
+        };
+
+        assert_eq!(gas_at(10) - gas_at(9), 5_000 - GasCosts::BALANCE);
+    }
 }
@@ -0,0 +1,329 @@
+//! Transaction-scoped world state for VM execution.
+//!
+//! Contract execution never writes to `StateManager` directly. Storage,
+//! balance, nonce and contract creation changes are buffered in a [`PendingState`] shared by every call
+//! frame of a transaction, and only committed once the top-level frame
+//! succeeds. Each change is journaled so a failing child frame can be rolled
+//! back without touching the caller's changes. Transient storage lives in
+//! the same pending state but is never committed, so it disappears with it.
+
+use crate::precompiles::Precompiles;
+use minichain_core::{contract_address, hash, Address};
+use minichain_storage::{StateManager, StorageError};
+use minichain_vm::{Host, PrecompileOutcome, Program, ProgramCache, StorageBackend};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A single undoable change.
+enum JournalEntry {
+    Storage {
+        contract: Address,
+        key: [u8; 32],
+        previous: Option<[u8; 32]>,
+    },
+    Transient {
+        contract: Address,
+        key: [u8; 32],
+        previous: Option<[u8; 32]>,
+    },
+    Balance {
+        address: Address,
+        previous: Option<u64>,
+    },
+    Nonce {
+        address: Address,
+        previous: Option<u64>,
+    },
+    Deployment {
+        address: Address,
+    },
+}
+
+/// Buffered state changes of one transaction.
+#[derive(Default)]
+pub(crate) struct PendingState {
+    pub(crate) storage: HashMap<(Address, [u8; 32]), [u8; 32]>,
+    /// TSTORE slots, discarded at the end of the transaction.
+    transient: HashMap<(Address, [u8; 32]), [u8; 32]>,
+    pub(crate) balances: HashMap<Address, u64>,
+    pub(crate) nonces: HashMap<Address, u64>,
+    /// Runtime code of contracts created by CREATE.
+    pub(crate) deployments: HashMap<Address, Vec<u8>>,
+    journal: Vec<JournalEntry>,
+}
+
+impl PendingState {
+    fn set_storage(&mut self, contract: Address, key: [u8; 32], value: [u8; 32]) {
+        let previous = self.storage.insert((contract, key), value);
+        self.journal.push(JournalEntry::Storage {
+            contract,
+            key,
+            previous,
+        });
+    }
+
+    fn set_transient(&mut self, contract: Address, key: [u8; 32], value: [u8; 32]) {
+        let previous = self.transient.insert((contract, key), value);
+        self.journal.push(JournalEntry::Transient {
+            contract,
+            key,
+            previous,
+        });
+    }
+
+    fn set_balance(&mut self, address: Address, balance: u64) {
+        let previous = self.balances.insert(address, balance);
+        self.journal
+            .push(JournalEntry::Balance { address, previous });
+    }
+
+    fn set_nonce(&mut self, address: Address, nonce: u64) {
+        let previous = self.nonces.insert(address, nonce);
+        self.journal.push(JournalEntry::Nonce { address, previous });
+    }
+
+    fn deploy(&mut self, address: Address, code: Vec<u8>) {
+        self.deployments.insert(address, code);
+        self.journal.push(JournalEntry::Deployment { address });
+    }
+
+    fn revert_to(&mut self, checkpoint: usize) {
+        while self.journal.len() > checkpoint {
+            match self.journal.pop().expect("journal length checked") {
+                JournalEntry::Storage {
+                    contract,
+                    key,
+                    previous,
+                } => match previous {
+                    Some(value) => {
+                        self.storage.insert((contract, key), value);
+                    }
+                    None => {
+                        self.storage.remove(&(contract, key));
+                    }
+                },
+                JournalEntry::Transient {
+                    contract,
+                    key,
+                    previous,
+                } => match previous {
+                    Some(value) => {
+                        self.transient.insert((contract, key), value);
+                    }
+                    None => {
+                        self.transient.remove(&(contract, key));
+                    }
+                },
+                JournalEntry::Balance { address, previous } => match previous {
+                    Some(balance) => {
+                        self.balances.insert(address, balance);
+                    }
+                    None => {
+                        self.balances.remove(&address);
+                    }
+                },
+                JournalEntry::Nonce { address, previous } => match previous {
+                    Some(nonce) => {
+                        self.nonces.insert(address, nonce);
+                    }
+                    None => {
+                        self.nonces.remove(&address);
+                    }
+                },
+                JournalEntry::Deployment { address } => {
+                    self.deployments.remove(&address);
+                }
+            }
+        }
+    }
+}
+
+/// Storage view of one contract layered over the pending transaction state.
+#[derive(Clone)]
+pub(crate) struct OverlayStorage<'a> {
+    state: &'a StateManager<'a>,
+    contract: Address,
+    pending: Rc<RefCell<PendingState>>,
+}
+
+impl<'a> OverlayStorage<'a> {
+    pub(crate) fn new(
+        state: &'a StateManager<'a>,
+        contract: Address,
+        pending: Rc<RefCell<PendingState>>,
+    ) -> Self {
+        Self {
+            state,
+            contract,
+            pending,
+        }
+    }
+}
+
+impl StorageBackend for OverlayStorage<'_> {
+    fn sload(&self, key: &[u8; 32]) -> [u8; 32] {
+        if let Some(value) = self.pending.borrow().storage.get(&(self.contract, *key)) {
+            return *value;
+        }
+        self.state
+            .sload(&self.contract, key)
+            .expect("contract storage read should not fail")
+    }
+
+    fn sstore(&mut self, key: &[u8; 32], value: &[u8; 32]) {
+        self.pending
+            .borrow_mut()
+            .set_storage(self.contract, *key, *value);
+    }
+
+    fn tload(&self, key: &[u8; 32]) -> [u8; 32] {
+        self.pending
+            .borrow()
+            .transient
+            .get(&(self.contract, *key))
+            .copied()
+            .unwrap_or([0u8; 32])
+    }
+
+    fn tstore(&mut self, key: &[u8; 32], value: &[u8; 32]) {
+        self.pending
+            .borrow_mut()
+            .set_transient(self.contract, *key, *value);
+    }
+}
+
+/// Load the decoded program deployed at `address`, decoding it on a cache miss.
+pub(crate) fn load_program(
+    state: &StateManager<'_>,
+    programs: &ProgramCache,
+    address: &Address,
+) -> Result<Option<Arc<Program>>, StorageError> {
+    let Some(code_hash) = state.get_account(address)?.code_hash else {
+        return Ok(None);
+    };
+    if let Some(program) = programs.get(&code_hash) {
+        return Ok(Some(program));
+    }
+    Ok(state
+        .get_code(&code_hash)?
+        .map(|code| programs.insert(code_hash, code)))
+}
+
+/// [`Host`] implementation backed by `StateManager` and the pending state.
+pub(crate) struct ChainHost<'a> {
+    state: &'a StateManager<'a>,
+    pending: Rc<RefCell<PendingState>>,
+    programs: Arc<ProgramCache>,
+    precompiles: Arc<Precompiles>,
+}
+
+impl<'a> ChainHost<'a> {
+    pub(crate) fn new(
+        state: &'a StateManager<'a>,
+        pending: Rc<RefCell<PendingState>>,
+        programs: Arc<ProgramCache>,
+        precompiles: Arc<Precompiles>,
+    ) -> Self {
+        Self {
+            state,
+            pending,
+            programs,
+            precompiles,
+        }
+    }
+
+    /// Credit an account in the pending state.
+    pub(crate) fn credit(&self, address: &Address, amount: u64) {
+        let balance = self.balance(address).saturating_add(amount);
+        self.pending.borrow_mut().set_balance(*address, balance);
+    }
+
+    /// Current nonce of `address`, including pending changes.
+    fn nonce(&self, address: &Address) -> u64 {
+        if let Some(nonce) = self.pending.borrow().nonces.get(address) {
+            return *nonce;
+        }
+        self.state
+            .get_nonce(address)
+            .expect("account read should not fail")
+    }
+}
+
+impl Host for ChainHost<'_> {
+    fn code(&self, address: &Address) -> Option<Vec<u8>> {
+        if let Some(code) = self.pending.borrow().deployments.get(address) {
+            return Some(code.clone());
+        }
+        self.state
+            .get_code_for_address(address)
+            .expect("contract code read should not fail")
+    }
+
+    fn program(&self, address: &Address) -> Option<Arc<Program>> {
+        if let Some(code) = self.pending.borrow().deployments.get(address) {
+            return Some(self.programs.insert(hash(code), code.clone()));
+        }
+        load_program(self.state, &self.programs, address)
+            .expect("contract code read should not fail")
+    }
+
+    fn precompile(&self, address: &Address, input: &[u8], gas: u64) -> Option<PrecompileOutcome> {
+        self.precompiles.call(address, input, gas)
+    }
+
+    fn storage(&self, address: &Address) -> Box<dyn StorageBackend + '_> {
+        Box::new(OverlayStorage::new(
+            self.state,
+            *address,
+            self.pending.clone(),
+        ))
+    }
+
+    fn balance(&self, address: &Address) -> u64 {
+        if let Some(balance) = self.pending.borrow().balances.get(address) {
+            return *balance;
+        }
+        self.state
+            .get_balance(address)
+            .expect("account read should not fail")
+    }
+
+    fn transfer(&self, from: &Address, to: &Address, amount: u64) -> bool {
+        let from_balance = self.balance(from);
+        if from_balance < amount {
+            return false;
+        }
+        self.pending
+            .borrow_mut()
+            .set_balance(*from, from_balance - amount);
+        self.credit(to, amount);
+        true
+    }
+
+    fn create(&self, creator: &Address, code: &[u8], value: u64) -> Option<Address> {
+        let nonce = self.nonce(creator);
+        let address = contract_address(creator, nonce);
+        self.pending
+            .borrow_mut()
+            .set_nonce(*creator, nonce.saturating_add(1));
+
+        if self.code(&address).is_some() || self.nonce(&address) > 0 {
+            return None;
+        }
+        if !self.transfer(creator, &address, value) {
+            return None;
+        }
+        self.pending.borrow_mut().deploy(address, code.to_vec());
+        Some(address)
+    }
+
+    fn checkpoint(&self) -> usize {
+        self.pending.borrow().journal.len()
+    }
+
+    fn revert_to(&self, checkpoint: usize) {
+        self.pending.borrow_mut().revert_to(checkpoint);
+    }
+}
@@ -4,6 +4,8 @@
 //! - **Consensus**: Proof of Authority block production and validation
 //! - **Mempool**: Transaction pool for pending transactions
 //! - **Executor**: Block execution engine
+//! - **Precompiles**: Native contracts at reserved addresses
+//! - **Syscalls**: Host functions contracts call through SYSCALL
 //! - **Storage**: Persistent state management
 //!
 //! # Example
@@ -22,6 +24,9 @@
 //! let config = BlockchainConfig {
 //!     consensus: PoAConfig::new(vec![keypair.address()], 5),
 //!     max_block_size: 1000,
+//!     max_return_size: minichain_vm::DEFAULT_MAX_RETURN_SIZE,
+//!     gas_schedules: minichain_vm::GasSchedules::default(),
+//!     chain_id: minichain_chain::DEFAULT_CHAIN_ID,
 //! };
 //!
 //! // Create blockchain
@@ -37,12 +42,17 @@
 
 pub mod blockchain;
 pub mod executor;
+mod host;
 pub mod mempool;
+pub mod precompiles;
+pub mod syscalls;
 
 // Re-export commonly used types
 pub use blockchain::{Blockchain, BlockchainConfig, BlockchainError, BlockchainStats};
 pub use executor::{
-    decode_deployment_payload, encode_deployment_payload, BlockExecutionResult, ContractQuery,
-    ContractQueryResult, ExecutionError, Executor, TransactionReceipt,
+    decode_deployment_payload, decode_revert_reason, encode_deployment_payload,
+    BlockExecutionResult, ContractQuery, ContractQueryResult, ExecutionError, Executor,
+    TransactionReceipt, DEFAULT_CHAIN_ID,
 };
 pub use mempool::{Mempool, MempoolConfig, MempoolError, MempoolStats};
+pub use precompiles::{Precompile, PrecompileError, Precompiles};
@@ -215,7 +215,7 @@ impl Mempool {
     /// Returns up to `limit` transactions.
     pub fn get_by_gas_price(&self, limit: usize) -> Vec<Transaction> {
         let mut txs: Vec<_> = self.transactions.values().cloned().collect();
-        txs.sort_by(|a, b| b.gas_price.cmp(&a.gas_price));
+        txs.sort_by_key(|tx| std::cmp::Reverse(tx.gas_price));
         txs.truncate(limit);
         txs
     }
@@ -0,0 +1,370 @@
+//! Precompiled contracts.
+//!
+//! Calls to a reserved low address (0x…01 to 0x…0F) run native Rust instead
+//! of bytecode. Each precompile prices its own input, so contracts get
+//! expensive primitives at a fair cost without new opcodes.
+//!
+//! Boolean results are returned as a u64 in 8 little-endian bytes, so
+//! contracts can read them with LOAD64.
+
+use minichain_core::merkle::{verify_proof, MerkleProof};
+use minichain_core::{hash, Address, Hash, PublicKey, Signature};
+use minichain_vm::PrecompileOutcome;
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// BLAKE3 hash of the input.
+pub const BLAKE3_ADDRESS: Address = reserved_address(0x01);
+/// ed25519 signature check over `pubkey (32) || signature (64) || message`.
+pub const ED25519_VERIFY_ADDRESS: Address = reserved_address(0x02);
+/// Merkle proof check over `root (32) || leaf (32) || (side (1) || sibling (32))*`.
+pub const MERKLE_VERIFY_ADDRESS: Address = reserved_address(0x03);
+/// `base^exp mod m` over big-endian integers.
+pub const MODEXP_ADDRESS: Address = reserved_address(0x04);
+
+/// Highest address byte reserved for precompiles.
+pub const MAX_PRECOMPILE_INDEX: u8 = 0x0F;
+
+/// Largest base, exponent or modulus MODEXP accepts, in bytes.
+pub const MAX_MODEXP_LEN: u64 = 1024;
+
+/// The reserved address `0x00…00{index}`.
+pub const fn reserved_address(index: u8) -> Address {
+    let mut bytes = [0u8; 20];
+    bytes[19] = index;
+    Address(bytes)
+}
+
+/// Whether `address` lies in the range reserved for precompiles.
+pub fn is_reserved(address: &Address) -> bool {
+    let [prefix @ .., last] = address.0;
+    prefix == [0u8; 19] && (1..=MAX_PRECOMPILE_INDEX).contains(&last)
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PrecompileError {
+    #[error("invalid precompile input: {0}")]
+    InvalidInput(&'static str),
+}
+
+/// A contract implemented natively.
+pub trait Precompile: Send + Sync {
+    /// Gas charged for running on `input`.
+    fn gas(&self, input: &[u8]) -> u64;
+
+    /// Compute the output for `input`.
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>, PrecompileError>;
+}
+
+/// Precompiles by address.
+#[derive(Clone, Default)]
+pub struct Precompiles {
+    contracts: HashMap<Address, Arc<dyn Precompile>>,
+}
+
+impl Precompiles {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// BLAKE3, ed25519 verification, Merkle proof verification and MODEXP.
+    pub fn standard() -> Self {
+        Self::new()
+            .with(BLAKE3_ADDRESS, Blake3)
+            .with(ED25519_VERIFY_ADDRESS, Ed25519Verify)
+            .with(MERKLE_VERIFY_ADDRESS, MerkleVerify)
+            .with(MODEXP_ADDRESS, ModExp)
+    }
+
+    /// Install `precompile` at `address`, replacing any previous one.
+    ///
+    /// # Panics
+    ///
+    /// If `address` is outside the reserved range.
+    pub fn with(mut self, address: Address, precompile: impl Precompile + 'static) -> Self {
+        assert!(
+            is_reserved(&address),
+            "precompile address {address:?} is not reserved"
+        );
+        self.contracts.insert(address, Arc::new(precompile));
+        self
+    }
+
+    /// The precompile at `address`, if any.
+    pub fn get(&self, address: &Address) -> Option<&dyn Precompile> {
+        self.contracts.get(address).map(|p| p.as_ref())
+    }
+
+    /// Run the precompile at `address` with `gas` available.
+    ///
+    /// A failed run, whether out of gas or on invalid input, uses all of `gas`.
+    pub fn call(&self, address: &Address, input: &[u8], gas: u64) -> Option<PrecompileOutcome> {
+        let precompile = self.get(address)?;
+        let required = precompile.gas(input);
+        let output = (required <= gas)
+            .then(|| precompile.run(input).ok())
+            .flatten();
+        Some(match output {
+            Some(output) => PrecompileOutcome {
+                success: true,
+                gas_used: required,
+                output,
+            },
+            None => PrecompileOutcome {
+                success: false,
+                gas_used: gas,
+                output: Vec::new(),
+            },
+        })
+    }
+}
+
+/// Number of 32-byte words needed to hold `len` bytes.
+fn words(len: usize) -> u64 {
+    len.div_ceil(32) as u64
+}
+
+fn bool_output(value: bool) -> Vec<u8> {
+    (value as u64).to_le_bytes().to_vec()
+}
+
+/// BLAKE3 hash: 60 gas plus 12 per input word.
+pub struct Blake3;
+
+impl Precompile for Blake3 {
+    fn gas(&self, input: &[u8]) -> u64 {
+        60 + 12 * words(input.len())
+    }
+
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+        Ok(hash(input).0.to_vec())
+    }
+}
+
+/// ed25519 verification: 3000 gas plus 12 per message word.
+///
+/// Returns 1 for a valid signature and 0 otherwise.
+pub struct Ed25519Verify;
+
+impl Precompile for Ed25519Verify {
+    fn gas(&self, input: &[u8]) -> u64 {
+        3000 + 12 * words(input.len().saturating_sub(96))
+    }
+
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+        if input.len() < 96 {
+            return Err(PrecompileError::InvalidInput(
+                "expected a 32-byte key and 64-byte signature",
+            ));
+        }
+        let (key, rest) = input.split_at(32);
+        let (signature, message) = rest.split_at(64);
+        let key: [u8; 32] = key.try_into().expect("split at 32");
+        let signature = Signature::from_bytes(signature.try_into().expect("split at 64"));
+        let valid = PublicKey::from_bytes(&key)
+            .and_then(|key| key.verify(message, &signature))
+            .is_ok();
+        Ok(bool_output(valid))
+    }
+}
+
+/// Merkle proof verification: 100 gas plus 60 per sibling.
+///
+/// A side byte of 1 puts the sibling on the right of the running hash, 0 on
+/// the left. Returns 1 if the proof leads to the root and 0 otherwise.
+pub struct MerkleVerify;
+
+const MERKLE_STEP_LEN: usize = 33;
+
+impl Precompile for MerkleVerify {
+    fn gas(&self, input: &[u8]) -> u64 {
+        let siblings = input.len().saturating_sub(64) / MERKLE_STEP_LEN;
+        100 + 60 * siblings as u64
+    }
+
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+        if input.len() < 64 || !(input.len() - 64).is_multiple_of(MERKLE_STEP_LEN) {
+            return Err(PrecompileError::InvalidInput(
+                "expected root, leaf and 33-byte proof steps",
+            ));
+        }
+        let hash_at = |offset: usize| Hash(input[offset..offset + 32].try_into().expect("32"));
+        let root = hash_at(0);
+        let mut proof = MerkleProof {
+            leaf: hash_at(32),
+            siblings: Vec::new(),
+            directions: Vec::new(),
+        };
+        for step in input[64..].chunks(MERKLE_STEP_LEN) {
+            let is_right = match step[0] {
+                0 => false,
+                1 => true,
+                _ => return Err(PrecompileError::InvalidInput("proof side must be 0 or 1")),
+            };
+            proof.directions.push(is_right);
+            proof.siblings.push(Hash(step[1..].try_into().expect("32")));
+        }
+        Ok(bool_output(verify_proof(&root, &proof)))
+    }
+}
+
+/// Modular exponentiation, priced like EIP-2565.
+///
+/// Input: base, exponent and modulus lengths as u64 little-endian, followed
+/// by the three big-endian numbers. Missing bytes read as zero. The output is
+/// the result as a big-endian number of the modulus length.
+pub struct ModExp;
+
+impl ModExp {
+    const HEADER_LEN: usize = 24;
+
+    fn lengths(input: &[u8]) -> [u64; 3] {
+        std::array::from_fn(|i| {
+            u64::from_le_bytes(read_padded(input, i * 8, 8).try_into().unwrap())
+        })
+    }
+}
+
+impl Precompile for ModExp {
+    fn gas(&self, input: &[u8]) -> u64 {
+        let [base_len, exp_len, mod_len] = Self::lengths(input);
+        let words = base_len.max(mod_len).div_ceil(8);
+        let complexity = words.saturating_mul(words);
+
+        // Bit length of the exponent's leading 32 bytes, plus 8 per byte after them.
+        let exp_start = Self::HEADER_LEN.saturating_add(base_len as usize);
+        let head_len = exp_len.min(32) as usize;
+        let head = BigUint::from_bytes_be(&read_padded(input, exp_start, head_len));
+        let iterations = (exp_len.saturating_sub(32))
+            .saturating_mul(8)
+            .saturating_add(head.bits().saturating_sub(1))
+            .max(1);
+
+        (complexity.saturating_mul(iterations) / 3).max(200)
+    }
+
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+        let [base_len, exp_len, mod_len] = Self::lengths(input);
+        if [base_len, exp_len, mod_len]
+            .iter()
+            .any(|&len| len > MAX_MODEXP_LEN)
+        {
+            return Err(PrecompileError::InvalidInput(
+                "operand longer than 1024 bytes",
+            ));
+        }
+        let (base_len, exp_len, mod_len) = (base_len as usize, exp_len as usize, mod_len as usize);
+
+        let mut offset = Self::HEADER_LEN;
+        let mut number = |len: usize| {
+            let value = BigUint::from_bytes_be(&read_padded(input, offset, len));
+            offset += len;
+            value
+        };
+        let base = number(base_len);
+        let exp = number(exp_len);
+        let modulus = number(mod_len);
+
+        let mut output = vec![0u8; mod_len];
+        if modulus != BigUint::ZERO {
+            let result = base.modpow(&exp, &modulus).to_bytes_be();
+            output[mod_len - result.len()..].copy_from_slice(&result);
+        }
+        Ok(output)
+    }
+}
+
+/// `len` bytes of `input` starting at `offset`, zero-padded past the end.
+fn read_padded(input: &[u8], offset: usize, len: usize) -> Vec<u8> {
+    let mut bytes = input.get(offset..).unwrap_or_default().to_vec();
+    bytes.resize(len, 0);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minichain_core::{Keypair, MerkleTree};
+
+    #[test]
+    fn test_reserved_range() {
+        assert!(is_reserved(&BLAKE3_ADDRESS));
+        assert!(is_reserved(&reserved_address(0x0F)));
+        assert!(!is_reserved(&reserved_address(0x10)));
+        assert!(!is_reserved(&Address::ZERO));
+    }
+
+    #[test]
+    fn test_ed25519_verify() {
+        let keypair = Keypair::generate();
+        let signature = keypair.sign(b"hello");
+        let mut input = keypair.public_key.as_bytes().to_vec();
+        input.extend_from_slice(signature.as_bytes());
+        input.extend_from_slice(b"hello");
+
+        assert_eq!(Ed25519Verify.run(&input).unwrap(), 1u64.to_le_bytes());
+        *input.last_mut().unwrap() ^= 1;
+        assert_eq!(Ed25519Verify.run(&input).unwrap(), 0u64.to_le_bytes());
+        assert!(Ed25519Verify.run(&input[..95]).is_err());
+    }
+
+    #[test]
+    fn test_merkle_verify() {
+        let leaves: Vec<Hash> = (0..5u8).map(|i| hash(&[i])).collect();
+        let tree = MerkleTree::new(&leaves);
+        let proof = tree.proof(3).unwrap();
+
+        let mut input = tree.root().0.to_vec();
+        input.extend_from_slice(&proof.leaf.0);
+        for (sibling, is_right) in proof.siblings.iter().zip(&proof.directions) {
+            input.push(*is_right as u8);
+            input.extend_from_slice(&sibling.0);
+        }
+
+        assert_eq!(MerkleVerify.run(&input).unwrap(), 1u64.to_le_bytes());
+        assert_eq!(
+            MerkleVerify.gas(&input),
+            100 + 60 * proof.siblings.len() as u64
+        );
+        input[0] ^= 1;
+        assert_eq!(MerkleVerify.run(&input).unwrap(), 0u64.to_le_bytes());
+        assert!(MerkleVerify.run(&input[..70]).is_err());
+    }
+
+    #[test]
+    fn test_modexp() {
+        // 3^5 mod 7 = 5, with a two-byte modulus.
+        let mut input = Vec::new();
+        for len in [1u64, 1, 2] {
+            input.extend_from_slice(&len.to_le_bytes());
+        }
+        input.extend_from_slice(&[3, 5, 0, 7]);
+
+        assert_eq!(ModExp.run(&input).unwrap(), vec![0, 5]);
+        assert_eq!(ModExp.gas(&input), 200);
+
+        // A zero modulus yields zero rather than failing.
+        let zero_mod = [&input[..input.len() - 1], &[0]].concat();
+        assert_eq!(ModExp.run(&zero_mod).unwrap(), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_call_charges_gas_and_fails_closed() {
+        let precompiles = Precompiles::standard();
+        let outcome = precompiles.call(&BLAKE3_ADDRESS, b"abc", 1_000).unwrap();
+        assert!(outcome.success);
+        assert_eq!(outcome.gas_used, 72);
+        assert_eq!(outcome.output, hash(b"abc").0);
+
+        let starved = precompiles.call(&BLAKE3_ADDRESS, b"abc", 71).unwrap();
+        assert!(!starved.success);
+        assert_eq!(starved.gas_used, 71);
+
+        assert!(precompiles
+            .call(&reserved_address(0x0F), b"abc", 1_000)
+            .is_none());
+    }
+}
@@ -0,0 +1,68 @@
+//! Host functions contracts reach through SYSCALL.
+//!
+//! Each function has a fixed id. Inputs are read from the memory range
+//! passed to SYSCALL; numbers are little-endian u64s, as with LOAD64.
+
+use crate::host::ChainHost;
+use minichain_core::Address;
+use minichain_vm::{BlockContext, GasSchedule, Host, HostFunctions, SyscallOutput, Vm};
+use std::rc::Rc;
+
+/// Balance of the account whose 20-byte address is the input.
+pub const SYSCALL_BALANCE: u64 = 0x01;
+/// Hash of the block whose number is the input. The value is 1 if the hash
+/// is known and 0 otherwise, in which case the data is 32 zero bytes.
+pub const SYSCALL_BLOCK_HASH: u64 = 0x02;
+
+/// Host functions of one transaction, backed by its pending state.
+pub(crate) struct ChainSyscalls<'a> {
+    host: Rc<ChainHost<'a>>,
+    block: BlockContext,
+    /// Schedule active at the block's height, which prices each function.
+    schedule: GasSchedule,
+}
+
+impl<'a> ChainSyscalls<'a> {
+    pub(crate) fn new(host: Rc<ChainHost<'a>>, block: BlockContext, schedule: GasSchedule) -> Self {
+        Self {
+            host,
+            block,
+            schedule,
+        }
+    }
+}
+
+impl HostFunctions for ChainSyscalls<'_> {
+    fn gas(&self, id: u64, _input: &[u8]) -> Option<u64> {
+        match id {
+            SYSCALL_BALANCE => Some(self.schedule.balance),
+            SYSCALL_BLOCK_HASH => Some(self.schedule.blockhash),
+            _ => None,
+        }
+    }
+
+    fn call(&self, _vm: &Vm<'_>, id: u64, input: &[u8]) -> Result<SyscallOutput, String> {
+        match id {
+            SYSCALL_BALANCE => {
+                let address: [u8; 20] = input
+                    .try_into()
+                    .map_err(|_| "expected a 20-byte address".to_string())?;
+                Ok(SyscallOutput {
+                    value: self.host.balance(&Address(address)),
+                    data: Vec::new(),
+                })
+            }
+            SYSCALL_BLOCK_HASH => {
+                let number: [u8; 8] = input
+                    .try_into()
+                    .map_err(|_| "expected an 8-byte block number".to_string())?;
+                let hash = self.block.block_hash(u64::from_le_bytes(number));
+                Ok(SyscallOutput {
+                    value: hash.is_some() as u64,
+                    data: hash.unwrap_or_default().0.to_vec(),
+                })
+            }
+            _ => Err(format!("unknown syscall {id}")),
+        }
+    }
+}
@@ -3,12 +3,16 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::Colorize;
-use minichain_chain::{Blockchain, BlockchainConfig};
+use minichain_assembler::assemble_with_labels;
+use minichain_chain::{Blockchain, BlockchainConfig, DEFAULT_CHAIN_ID};
 use minichain_consensus::PoAConfig;
 use minichain_core::{Address, Transaction};
 use minichain_storage::Storage;
+use minichain_vm::{GasProfiler, GasSchedules, DEFAULT_MAX_RETURN_SIZE};
+use std::cell::RefCell;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use crate::alias;
 
@@ -45,6 +49,18 @@ pub struct CallArgs {
     /// Gas price
     #[arg(long, default_value = "1")]
     gas_price: u64,
+
+    /// Print a gas profile of the query
+    #[arg(long, requires = "query")]
+    profile: bool,
+
+    /// Assembly source of the contract, to break the profile down by label
+    #[arg(long, requires = "profile")]
+    source: Option<PathBuf>,
+
+    /// Write the profile as folded stacks for flamegraph tools
+    #[arg(long, requires = "profile")]
+    flamegraph: Option<PathBuf>,
 }
 
 pub fn run(args: CallArgs) -> Result<()> {
@@ -88,8 +104,34 @@ pub fn run(args: CallArgs) -> Result<()> {
     if args.query {
         // Query mode executes immediately against current state and prints return data.
         let blockchain = Blockchain::new(&storage, config);
-        let result = blockchain.query_contract(&to, from, &data, args.amount, args.gas_limit)?;
+        let profiler = if args.profile {
+            Some(Rc::new(RefCell::new(load_profiler(
+                to,
+                args.source.as_deref(),
+            )?)))
+        } else {
+            None
+        };
+        let result = match &profiler {
+            Some(profiler) => blockchain.query_contract_with_inspector(
+                &to,
+                from,
+                &data,
+                args.amount,
+                args.gas_limit,
+                profiler.clone(),
+            )?,
+            None => blockchain.query_contract(&to, from, &data, args.amount, args.gas_limit)?,
+        };
+        if let Some(profiler) = &profiler {
+            report_profile(&profiler.borrow(), args.flamegraph.as_deref())?;
+        }
         if !result.success {
+            if let Some(reason) = result.revert_reason() {
+                println!("  Revert Reason: {}", reason.bright_red());
+            } else if !result.revert_data.is_empty() {
+                println!("  Revert Data: 0x{}", hex::encode(&result.revert_data));
+            }
             anyhow::bail!(
                 "Query failed: {}",
                 result
@@ -173,6 +215,31 @@ pub fn run(args: CallArgs) -> Result<()> {
     Ok(())
 }
 
+// Helper function to create a profiler, labelled from the contract source if given
+fn load_profiler(contract: Address, source: Option<&Path>) -> Result<GasProfiler> {
+    let profiler = GasProfiler::new();
+    let Some(source) = source else {
+        return Ok(profiler);
+    };
+    let code = fs::read_to_string(source)
+        .with_context(|| format!("Failed to read source file: {}", source.display()))?;
+    let (_, labels) =
+        assemble_with_labels(&code).with_context(|| "Failed to compile assembly code")?;
+    Ok(profiler.with_labels(contract, labels))
+}
+
+// Helper function to print the profile table and write the flamegraph input
+fn report_profile(profiler: &GasProfiler, flamegraph: Option<&Path>) -> Result<()> {
+    println!("{}", profiler.table());
+    if let Some(path) = flamegraph {
+        fs::write(path, profiler.folded())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!("  Folded stacks written to {}", path.display());
+        println!();
+    }
+    Ok(())
+}
+
 // Helper function to load blockchain config
 fn load_config(data_dir: &Path) -> Result<BlockchainConfig> {
     let config_file = data_dir.join("config.json");
@@ -198,10 +265,25 @@ fn load_config(data_dir: &Path) -> Result<BlockchainConfig> {
         .get("max_block_size")
         .and_then(|v| v.as_u64())
         .unwrap_or(1000) as usize;
+    let max_return_size = json
+        .get("max_return_size")
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_MAX_RETURN_SIZE, |v| v as usize);
+    let gas_schedules = match json.get("gas_schedules") {
+        Some(value) => serde_json::from_value(value.clone()).context("Invalid gas_schedules")?,
+        None => GasSchedules::default(),
+    };
+    let chain_id = json
+        .get("chain_id")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_CHAIN_ID);
 
     Ok(BlockchainConfig {
         consensus: PoAConfig::new(authorities, block_time),
         max_block_size,
+        max_return_size,
+        gas_schedules,
+        chain_id,
     })
 }
 
@@ -4,10 +4,11 @@ use anyhow::{Context, Result};
 use clap::Args;
 use colored::Colorize;
 use minichain_assembler::assemble;
-use minichain_chain::{encode_deployment_payload, Blockchain, BlockchainConfig};
+use minichain_chain::{encode_deployment_payload, Blockchain, BlockchainConfig, DEFAULT_CHAIN_ID};
 use minichain_consensus::PoAConfig;
 use minichain_core::{Address, Transaction};
 use minichain_storage::Storage;
+use minichain_vm::{GasSchedules, DEFAULT_MAX_RETURN_SIZE};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -190,10 +191,25 @@ fn load_config(data_dir: &Path) -> Result<BlockchainConfig> {
         .get("max_block_size")
         .and_then(|v| v.as_u64())
         .unwrap_or(1000) as usize;
+    let max_return_size = json
+        .get("max_return_size")
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_MAX_RETURN_SIZE, |v| v as usize);
+    let gas_schedules = match json.get("gas_schedules") {
+        Some(value) => serde_json::from_value(value.clone()).context("Invalid gas_schedules")?,
+        None => GasSchedules::default(),
+    };
+    let chain_id = json
+        .get("chain_id")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_CHAIN_ID);
 
     Ok(BlockchainConfig {
         consensus: PoAConfig::new(authorities, block_time),
         max_block_size,
+        max_return_size,
+        gas_schedules,
+        chain_id,
     })
 }
 
@@ -3,10 +3,11 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::Colorize;
-use minichain_chain::{Blockchain, BlockchainConfig};
+use minichain_chain::{Blockchain, BlockchainConfig, DEFAULT_CHAIN_ID};
 use minichain_consensus::PoAConfig;
 use minichain_core::{Block, Keypair};
 use minichain_storage::{ChainStore, Storage};
+use minichain_vm::{GasSchedules, DEFAULT_MAX_RETURN_SIZE};
 use std::fs;
 use std::path::PathBuf;
 
@@ -86,6 +87,9 @@ pub fn run(args: InitArgs) -> Result<()> {
     let config = BlockchainConfig {
         consensus: PoAConfig::new(authorities.clone(), args.block_time),
         max_block_size: 1000,
+        max_return_size: DEFAULT_MAX_RETURN_SIZE,
+        gas_schedules: GasSchedules::default(),
+        chain_id: DEFAULT_CHAIN_ID,
     };
 
     // Create blockchain
@@ -139,6 +143,9 @@ pub fn run(args: InitArgs) -> Result<()> {
         "authorities": authorities.iter().map(|a| a.to_hex()).collect::<Vec<_>>(),
         "block_time": args.block_time,
         "max_block_size": 1000,
+        "max_return_size": DEFAULT_MAX_RETURN_SIZE,
+        "chain_id": DEFAULT_CHAIN_ID,
+        "gas_schedules": GasSchedules::default(),
     });
 
     fs::write(&config_file, serde_json::to_string_pretty(&config_json)?)?;
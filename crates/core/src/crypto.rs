@@ -195,6 +195,13 @@ impl PublicKey {
         Address(addr)
     }
 
+    /// Parse a public key from its raw 32-byte encoding.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self, CryptoError> {
+        VerifyingKey::from_bytes(bytes)
+            .map(PublicKey)
+            .map_err(|_| CryptoError::InvalidPublicKey)
+    }
+
     /// Get the raw bytes of the public key.
     pub fn as_bytes(&self) -> [u8; 32] {
         self.0.to_bytes()
@@ -319,6 +326,13 @@ mod tests {
         assert!(kp2.verify(b"hello", &sig).is_err());
     }
 
+    #[test]
+    fn test_public_key_bytes_roundtrip() {
+        let keypair = Keypair::generate();
+        let bytes = keypair.public_key.as_bytes();
+        assert_eq!(PublicKey::from_bytes(&bytes).unwrap(), keypair.public_key);
+    }
+
     #[test]
     fn test_address_hex_roundtrip() {
         let kp = Keypair::generate();
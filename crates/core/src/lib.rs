@@ -5,12 +5,14 @@
 //! - Account state
 //! - Transactions
 //! - Blocks and block headers
+//! - Contract event logs
 //! - Merkle trees
 
 pub mod account;
 pub mod block;
 pub mod crypto;
 pub mod hash;
+pub mod log;
 pub mod merkle;
 pub mod transaction;
 
@@ -19,5 +21,6 @@ pub use account::Account;
 pub use block::{Block, BlockHeader};
 pub use crypto::{Address, CryptoError, Keypair, PublicKey, Signature};
 pub use hash::{hash, hash_concat, Hash, H256};
+pub use log::{Log, MAX_LOG_TOPICS};
 pub use merkle::{merkle_root, MerkleProof, MerkleTree};
-pub use transaction::{Transaction, TransactionError};
+pub use transaction::{contract_address, Transaction, TransactionError};
@@ -0,0 +1,47 @@
+//! Contract event logs.
+
+use crate::crypto::Address;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of topics a single log may carry (LOG0–LOG4).
+pub const MAX_LOG_TOPICS: usize = 4;
+
+/// An event emitted by a contract through one of the LOG0–LOG4 instructions.
+///
+/// Topics are register-sized values intended for filtering (e.g. an event
+/// selector followed by indexed arguments). Everything else goes in `data`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Log {
+    /// Contract that emitted the event.
+    pub address: Address,
+    /// Indexed topics, at most [`MAX_LOG_TOPICS`].
+    pub topics: Vec<u64>,
+    /// Unindexed payload copied from contract memory.
+    pub data: Vec<u8>,
+}
+
+impl Log {
+    /// Create a new log entry.
+    pub fn new(address: Address, topics: Vec<u64>, data: Vec<u8>) -> Self {
+        Self {
+            address,
+            topics,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_serialization_roundtrip() {
+        let log = Log::new(Address([0xAB; 20]), vec![1, 2], vec![0xDE, 0xAD]);
+
+        let encoded = bincode::serialize(&log).unwrap();
+        let decoded: Log = bincode::deserialize(&encoded).unwrap();
+
+        assert_eq!(decoded, log);
+    }
+}
@@ -177,17 +177,23 @@ impl Transaction {
         if !self.is_deploy() {
             return None;
         }
-        // Contract address = first 20 bytes of hash(sender || nonce)
-        let mut data = Vec::new();
-        data.extend_from_slice(&self.from.0);
-        data.extend_from_slice(&self.nonce.to_le_bytes());
-        let h = hash(&data);
-        let mut addr = [0u8; 20];
-        addr.copy_from_slice(&h.0[..20]);
-        Some(Address(addr))
+        Some(contract_address(&self.from, self.nonce))
     }
 }
 
+/// Address of the contract `creator` deploys at `nonce`.
+///
+/// Contract address = first 20 bytes of hash(creator || nonce).
+pub fn contract_address(creator: &Address, nonce: u64) -> Address {
+    let mut data = Vec::new();
+    data.extend_from_slice(&creator.0);
+    data.extend_from_slice(&nonce.to_le_bytes());
+    let h = hash(&data);
+    let mut addr = [0u8; 20];
+    addr.copy_from_slice(&h.0[..20]);
+    Address(addr)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,9 +1,10 @@
 use anyhow::{Context, Result};
 use minichain_assembler::assemble;
-use minichain_chain::{Blockchain, BlockchainConfig};
+use minichain_chain::{Blockchain, BlockchainConfig, DEFAULT_CHAIN_ID};
 use minichain_consensus::{BlockProposer, PoAConfig};
 use minichain_core::{Address, Block, Hash, Keypair, Transaction};
 use minichain_storage::{ChainStore, StateManager, Storage};
+use minichain_vm::{ExecutionStatus, GasSchedules, DEFAULT_MAX_RETURN_SIZE};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
@@ -44,6 +45,17 @@ pub struct TransactionInfo {
     pub data: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractCallResult {
+    pub success: bool,
+    pub status: Option<ExecutionStatus>,
+    pub gas_used: u64,
+    pub return_data: String,
+    pub revert_data: String,
+    pub revert_reason: Option<String>,
+    pub error: Option<String>,
+}
+
 pub fn init_blockchain(data_dir: &Path, authorities: usize, block_time: u64) -> Result<String> {
     fs::create_dir_all(data_dir)?;
 
@@ -66,6 +78,9 @@ pub fn init_blockchain(data_dir: &Path, authorities: usize, block_time: u64) ->
     let config = BlockchainConfig {
         consensus: PoAConfig::new(authority_addresses.clone(), block_time),
         max_block_size: 1000,
+        max_return_size: DEFAULT_MAX_RETURN_SIZE,
+        gas_schedules: GasSchedules::default(),
+        chain_id: DEFAULT_CHAIN_ID,
     };
 
     let mut blockchain = Blockchain::new(&storage, config);
@@ -97,6 +112,9 @@ pub fn init_blockchain(data_dir: &Path, authorities: usize, block_time: u64) ->
         "authorities": authority_addresses.iter().map(|a| a.to_hex()).collect::<Vec<_>>(),
         "block_time": block_time,
         "max_block_size": 1000,
+        "max_return_size": DEFAULT_MAX_RETURN_SIZE,
+        "chain_id": DEFAULT_CHAIN_ID,
+        "gas_schedules": GasSchedules::default(),
     });
     fs::write(&config_file, serde_json::to_string_pretty(&config_json)?)?;
 
@@ -524,6 +542,41 @@ pub fn call_contract(
     Ok(tx_hash.to_hex())
 }
 
+pub fn query_contract(
+    data_dir: &Path,
+    from_name: &str,
+    to_address: &str,
+    data: Option<&str>,
+    amount: u64,
+    gas_limit: u64,
+) -> Result<ContractCallResult> {
+    let keys_dir = data_dir.join("keys");
+    let from = load_keypair(&keys_dir, from_name)?.address();
+
+    let to = Address::from_hex(to_address).context("Invalid contract address")?;
+
+    let calldata = if let Some(d) = data {
+        hex::decode(d).context("Invalid calldata hex")?
+    } else {
+        vec![]
+    };
+
+    let storage = Storage::open(data_dir)?;
+    let config = load_config(data_dir)?;
+    let blockchain = Blockchain::new(&storage, config);
+    let result = blockchain.query_contract(&to, from, &calldata, amount, gas_limit)?;
+
+    Ok(ContractCallResult {
+        success: result.success,
+        gas_used: result.gas_used,
+        return_data: hex::encode(&result.return_data),
+        revert_data: hex::encode(&result.revert_data),
+        revert_reason: result.revert_reason().map(str::to_string),
+        status: result.status,
+        error: result.error,
+    })
+}
+
 fn load_keypair(keys_dir: &Path, name: &str) -> Result<Keypair> {
     let key_file = keys_dir.join(format!("{}.json", name));
     let contents = fs::read_to_string(&key_file)?;
@@ -568,10 +621,25 @@ fn load_config(data_dir: &Path) -> Result<BlockchainConfig> {
         .get("max_block_size")
         .and_then(|v| v.as_u64())
         .unwrap_or(1000) as usize;
+    let max_return_size = json
+        .get("max_return_size")
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_MAX_RETURN_SIZE, |v| v as usize);
+    let gas_schedules = match json.get("gas_schedules") {
+        Some(value) => serde_json::from_value(value.clone()).context("Invalid gas_schedules")?,
+        None => GasSchedules::default(),
+    };
+    let chain_id = json
+        .get("chain_id")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_CHAIN_ID);
 
     Ok(BlockchainConfig {
         consensus: PoAConfig::new(authorities, block_time),
         max_block_size,
+        max_return_size,
+        gas_schedules,
+        chain_id,
     })
 }
 
@@ -723,6 +791,18 @@ mod tests {
         drop(temp_dir);
     }
 
+    #[test]
+    fn test_query_contract_without_code() {
+        let (temp_dir, data_dir) = create_test_env();
+        create_account(&data_dir, Some("alice")).unwrap();
+        let target = Address::from_bytes([0x42; 20]).to_hex();
+        let result = query_contract(&data_dir, "alice", &target, None, 0, 100_000).unwrap();
+        assert!(!result.success);
+        assert!(result.revert_reason.is_none());
+        assert!(result.error.is_some());
+        drop(temp_dir);
+    }
+
     #[test]
     fn test_list_mempool() {
         let (temp_dir, data_dir) = create_test_env();
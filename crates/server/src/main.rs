@@ -7,7 +7,9 @@ use clap::Parser;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 
-use minichain_server::api::{self, AccountInfo, BlockInfo, KeypairInfo, TransactionInfo};
+use minichain_server::api::{
+    self, AccountInfo, BlockInfo, ContractCallResult, KeypairInfo, TransactionInfo,
+};
 
 #[derive(Clone)]
 struct AppState {
@@ -130,6 +132,16 @@ struct CallRequest {
     data: Option<String>,
     amount: Option<u64>,
     gas_price: Option<u64>,
+    gas_limit: Option<u64>,
+    query: Option<bool>,
+}
+
+/// Either the hash of a submitted call or the result of a read-only query.
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+enum CallResponse {
+    Submitted(String),
+    Query(ContractCallResult),
 }
 
 fn get_data_dir(data_dir: &Option<String>, default: &Path) -> PathBuf {
@@ -419,19 +431,33 @@ async fn deploy_contract(
 async fn call_contract(
     State(state): State<AppState>,
     Json(req): Json<CallRequest>,
-) -> Json<ApiResponse<String>> {
+) -> Json<ApiResponse<CallResponse>> {
     let data_dir = get_data_dir(&req.data_dir, &state.data_dir);
     let gas_price = req.gas_price.unwrap_or(1);
     let amount = req.amount.unwrap_or(0);
-    match api::call_contract(
-        &data_dir,
-        &req.from,
-        &req.to,
-        req.data.as_deref(),
-        amount,
-        gas_price,
-    ) {
-        Ok(hash) => Json(ApiResponse::ok(hash)),
+    let result = if req.query.unwrap_or(false) {
+        api::query_contract(
+            &data_dir,
+            &req.from,
+            &req.to,
+            req.data.as_deref(),
+            amount,
+            req.gas_limit.unwrap_or(250_000),
+        )
+        .map(CallResponse::Query)
+    } else {
+        api::call_contract(
+            &data_dir,
+            &req.from,
+            &req.to,
+            req.data.as_deref(),
+            amount,
+            gas_price,
+        )
+        .map(CallResponse::Submitted)
+    };
+    match result {
+        Ok(response) => Json(ApiResponse::ok(response)),
         Err(e) => Json(ApiResponse::err(e.to_string())),
     }
 }
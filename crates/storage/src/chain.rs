@@ -1,7 +1,7 @@
 //! Block storage and chain state management.
 
 use crate::db::{Result, Storage, StorageError};
-use minichain_core::{Block, Hash};
+use minichain_core::{Block, Hash, Log};
 
 /// Keys for chain metadata.
 const CHAIN_HEAD_KEY: &[u8] = b"chain:head";
@@ -70,6 +70,22 @@ impl<'a> ChainStore<'a> {
         self.storage.contains(key)
     }
 
+    // =========================================================================
+    // Event Logs
+    // =========================================================================
+
+    /// Store the event logs emitted by a block, one entry per transaction.
+    pub fn put_block_logs(&self, hash: &Hash, logs: &[Vec<Log>]) -> Result<()> {
+        let key = Storage::block_logs_key(hash);
+        self.storage.put(key, &logs)
+    }
+
+    /// Get the event logs of a block, one entry per transaction.
+    pub fn get_block_logs(&self, hash: &Hash) -> Result<Option<Vec<Vec<Log>>>> {
+        let key = Storage::block_logs_key(hash);
+        self.storage.get(key)
+    }
+
     // =========================================================================
     // Chain Head Tracking
     // =========================================================================
@@ -420,4 +436,21 @@ mod tests {
         assert!(chain.has_block(&hash).unwrap());
         assert!(!chain.has_block(&Hash::ZERO).unwrap());
     }
+
+    #[test]
+    fn test_block_logs_roundtrip() {
+        let storage = setup();
+        let chain = ChainStore::new(&storage);
+        let hash = genesis_block().hash();
+
+        assert_eq!(chain.get_block_logs(&hash).unwrap(), None);
+
+        let logs = vec![
+            vec![Log::new(Address([0x01; 20]), vec![7], vec![1, 2, 3])],
+            vec![],
+        ];
+        chain.put_block_logs(&hash, &logs).unwrap();
+
+        assert_eq!(chain.get_block_logs(&hash).unwrap(), Some(logs));
+    }
 }
@@ -192,6 +192,14 @@ impl Storage {
         key
     }
 
+    /// Create a prefixed key for the event logs of a block.
+    /// Format: "logs:" + block_hash_bytes
+    pub fn block_logs_key(hash: &Hash) -> Vec<u8> {
+        let mut key = b"logs:".to_vec();
+        key.extend_from_slice(&hash.0);
+        key
+    }
+
     /// Create a prefixed key for contract storage.
     /// Format: "storage:" + contract_address + ":" + slot
     pub fn contract_storage_key(contract: &Address, slot: &[u8]) -> Vec<u8> {
@@ -219,7 +219,7 @@ impl<'a> StateManager<'a> {
 
         // Sort for deterministic ordering
         // (sled iteration order may vary, so we sort by hash)
-        account_hashes.sort_by(|a, b| a.0.cmp(&b.0));
+        account_hashes.sort_by_key(|a| a.0);
 
         // Compute merkle root
         Ok(merkle_root(&account_hashes))
@@ -0,0 +1,127 @@
+//! Interpreter benchmarks.
+//!
+//! Run with `cargo bench -p minichain-vm`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use minichain_assembler::assemble;
+use minichain_core::Address;
+use minichain_vm::{GasSchedule, Program, StorageBackend, Vm};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const GAS_LIMIT: u64 = 100_000_000;
+
+/// Sums 0..10_000 in a tight register loop.
+const LOOP_SOURCE: &str = r#"
+    .entry main
+    main:
+        LOADI R0, 0          ; i
+        LOADI R1, 10000      ; n
+        LOADI R2, 0          ; sum
+        LOADI R3, 1
+        LOADI R5, loop
+        LOADI R6, done
+    loop:
+        LT R4, R0, R1
+        ISZERO R4, R4
+        JUMPI R4, R6
+        ADD R2, R2, R0
+        ADD R0, R0, R3
+        JUMP R5
+    done:
+        LOG R2
+        HALT
+"#;
+
+/// Increments 500 storage slots, reading each one back.
+const STORAGE_SOURCE: &str = r#"
+    .entry main
+    main:
+        LOADI R0, 0          ; slot
+        LOADI R1, 500        ; slot count
+        LOADI R3, 1
+        LOADI R5, loop
+        LOADI R6, done
+    loop:
+        LT R4, R0, R1
+        ISZERO R4, R4
+        JUMPI R4, R6
+        SLOAD R2, R0
+        ADD R2, R2, R3
+        SSTORE R0, R2
+        ADD R0, R0, R3
+        JUMP R5
+    done:
+        HALT
+"#;
+
+#[derive(Default)]
+struct MapStorage {
+    slots: HashMap<[u8; 32], [u8; 32]>,
+    transient: HashMap<[u8; 32], [u8; 32]>,
+}
+
+impl StorageBackend for MapStorage {
+    fn sload(&self, key: &[u8; 32]) -> [u8; 32] {
+        self.slots.get(key).copied().unwrap_or([0u8; 32])
+    }
+
+    fn sstore(&mut self, key: &[u8; 32], value: &[u8; 32]) {
+        self.slots.insert(*key, *value);
+    }
+
+    fn tload(&self, key: &[u8; 32]) -> [u8; 32] {
+        self.transient.get(key).copied().unwrap_or([0u8; 32])
+    }
+
+    fn tstore(&mut self, key: &[u8; 32], value: &[u8; 32]) {
+        self.transient.insert(*key, *value);
+    }
+}
+
+fn vm(program: Arc<Program>) -> Vm<'static> {
+    let mut vm = Vm::from_program(
+        program,
+        GAS_LIMIT,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    vm.set_storage(Box::new(MapStorage::default()));
+    vm
+}
+
+fn bench_contract(c: &mut Criterion, name: &str, source: &str) {
+    let code = assemble(source).expect("benchmark source assembles");
+    let program = Arc::new(Program::new(code.clone()));
+    assert!(vm(program.clone()).run().unwrap().success);
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("cached", |b| {
+        b.iter_batched(
+            || vm(program.clone()),
+            |mut vm| black_box(vm.run().unwrap()),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("decode_each_run", |b| {
+        b.iter_batched(
+            || code.clone(),
+            |code| black_box(vm(Arc::new(Program::new(code))).run().unwrap()),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn loops(c: &mut Criterion) {
+    bench_contract(c, "loop", LOOP_SOURCE);
+}
+
+fn storage(c: &mut Criterion) {
+    bench_contract(c, "storage", STORAGE_SOURCE);
+}
+
+criterion_group!(benches, loops, storage);
+criterion_main!(benches);
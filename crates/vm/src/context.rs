@@ -0,0 +1,69 @@
+//! Block and transaction context exposed to contracts.
+//!
+//! The embedder fills these in once per block and transaction; every call
+//! frame of a transaction sees the same values.
+
+use minichain_core::{Address, Hash};
+use std::sync::Arc;
+
+/// Number of preceding blocks whose hashes BLOCKHASH can return.
+pub const BLOCKHASH_WINDOW: u64 = 256;
+
+/// The block a transaction executes in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockContext {
+    /// Height, read by BLOCKNUMBER.
+    pub number: u64,
+    /// Block timestamp, read by TIMESTAMP.
+    pub timestamp: u64,
+    /// Block author, read by COINBASE.
+    pub author: Address,
+    /// Chain identifier, read by CHAINID.
+    pub chain_id: u64,
+    /// Hashes of the blocks directly before this one, oldest first, at most
+    /// [`BLOCKHASH_WINDOW`] of them.
+    pub recent_hashes: Arc<[Hash]>,
+}
+
+impl BlockContext {
+    /// Hash of block `number`, if it is one of the recent blocks before this one.
+    pub fn block_hash(&self, number: u64) -> Option<Hash> {
+        let age = self.number.checked_sub(number).filter(|&age| age > 0)?;
+        if age > BLOCKHASH_WINDOW {
+            return None;
+        }
+        let index = self.recent_hashes.len().checked_sub(age as usize)?;
+        Some(self.recent_hashes[index])
+    }
+}
+
+/// The transaction being executed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxContext {
+    /// Account that signed the transaction, read by ORIGIN.
+    pub origin: Address,
+    /// Price paid per unit of gas, read by GASPRICE.
+    pub gas_price: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minichain_core::hash;
+
+    #[test]
+    fn test_block_hash_window() {
+        let hashes: Vec<Hash> = (0..3u8).map(|i| hash(&[i])).collect();
+        let block = BlockContext {
+            number: 10,
+            recent_hashes: hashes.clone().into(),
+            ..BlockContext::default()
+        };
+
+        assert_eq!(block.block_hash(9), Some(hashes[2]));
+        assert_eq!(block.block_hash(7), Some(hashes[0]));
+        assert_eq!(block.block_hash(6), None);
+        assert_eq!(block.block_hash(10), None);
+        assert_eq!(block.block_hash(11), None);
+    }
+}
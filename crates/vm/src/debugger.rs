@@ -0,0 +1,321 @@
+//! Interactive step debugger.
+//!
+//! [`Debugger`] drives a [`Vm`] one instruction at a time and pauses on
+//! breakpoints and watchpoints, so tools can inspect registers, memory, gas
+//! and pending storage writes between steps.
+//!
+//! Only the top-level frame is stepped. A CALL runs the callee to completion
+//! as a single step, so breakpoints and watchpoints never fire inside it.
+
+use crate::executor::{ExecutionResult, Vm, VmError};
+use std::collections::{BTreeSet, HashMap};
+
+/// A location whose value is compared before and after every step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Watchpoint {
+    /// A register, R0-R15.
+    Register(u8),
+    /// `len` bytes of memory starting at `offset`. Bytes past the VM's
+    /// maximum memory size are not watched.
+    Memory { offset: u32, len: u32 },
+    /// A storage slot of the executing contract.
+    Storage([u8; 32]),
+}
+
+/// Why the debugger handed control back to the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    /// A single step completed.
+    Step,
+    /// Execution reached a breakpoint at this PC.
+    Breakpoint(usize),
+    /// A watched value changed. Register values are big-endian.
+    Watchpoint {
+        watchpoint: Watchpoint,
+        old: Vec<u8>,
+        new: Vec<u8>,
+    },
+    /// Execution halted or ran off the end of the bytecode.
+    Finished,
+}
+
+/// Step debugger wrapping a configured [`Vm`].
+pub struct Debugger<'a> {
+    vm: Vm<'a>,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: Vec<Watchpoint>,
+    labels: HashMap<String, usize>,
+}
+
+impl<'a> Debugger<'a> {
+    /// Wrap a VM that has its storage, host and calldata already set.
+    pub fn new(vm: Vm<'a>) -> Self {
+        Self {
+            vm,
+            breakpoints: BTreeSet::new(),
+            watchpoints: Vec::new(),
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Attach a label → PC source map, e.g. from `assemble_with_labels`.
+    pub fn with_labels(mut self, labels: HashMap<String, usize>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// The wrapped VM, for inspecting registers, memory, gas and storage writes.
+    pub fn vm(&self) -> &Vm<'a> {
+        &self.vm
+    }
+
+    /// Consume the debugger and return the VM.
+    pub fn into_vm(self) -> Vm<'a> {
+        self.vm
+    }
+
+    /// Resolve a label from the source map.
+    pub fn label_pc(&self, label: &str) -> Option<usize> {
+        self.labels.get(label).copied()
+    }
+
+    /// Label at `pc`, if the source map has one.
+    pub fn label_at(&self, pc: usize) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(_, &addr)| addr == pc)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Pause before executing the instruction at `pc`.
+    pub fn add_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.insert(pc);
+    }
+
+    /// Pause at a label. Returns the resolved PC, or `None` for an unknown label.
+    pub fn add_label_breakpoint(&mut self, label: &str) -> Option<usize> {
+        let pc = self.label_pc(label)?;
+        self.breakpoints.insert(pc);
+        Some(pc)
+    }
+
+    /// Remove a breakpoint. Returns false if none was set at `pc`.
+    pub fn remove_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    /// Currently set breakpoints, in PC order.
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Pause whenever the watched value changes.
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) {
+        if !self.watchpoints.contains(&watchpoint) {
+            self.watchpoints.push(watchpoint);
+        }
+    }
+
+    /// Remove a watchpoint. Returns false if it was not set.
+    pub fn remove_watchpoint(&mut self, watchpoint: &Watchpoint) -> bool {
+        let len = self.watchpoints.len();
+        self.watchpoints.retain(|w| w != watchpoint);
+        self.watchpoints.len() != len
+    }
+
+    /// Currently set watchpoints.
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    /// Current value of a watched location.
+    pub fn watch_value(&self, watchpoint: &Watchpoint) -> Vec<u8> {
+        match watchpoint {
+            Watchpoint::Register(r) => self.vm.get_registers()[*r as usize & 0x0F]
+                .to_be_bytes()
+                .to_vec(),
+            Watchpoint::Memory { offset, len } => {
+                // Memory can never reach past its maximum size, so there is
+                // nothing to compare beyond it.
+                let memory = self.vm.memory();
+                let len = (*len as usize).min(memory.max_size().saturating_sub(*offset as usize));
+                // Unallocated memory reads as zero, so growth alone is not a change.
+                let mut bytes = memory.read_range(*offset, len as u32);
+                bytes.resize(len, 0);
+                bytes
+            }
+            Watchpoint::Storage(key) => self.vm.peek_storage(key).to_vec(),
+        }
+    }
+
+    /// Execute one instruction of the top-level frame.
+    ///
+    /// Watchpoints are reported before breakpoints; if several watched values
+    /// change in the same step, the first one set wins.
+    pub fn step(&mut self) -> Result<StopReason, VmError> {
+        if self.vm.is_finished() {
+            return Ok(StopReason::Finished);
+        }
+
+        let before: Vec<Vec<u8>> = self
+            .watchpoints
+            .iter()
+            .map(|w| self.watch_value(w))
+            .collect();
+
+        self.vm.step()?;
+
+        for (watchpoint, old) in self.watchpoints.iter().zip(before) {
+            let new = self.watch_value(watchpoint);
+            if new != old {
+                return Ok(StopReason::Watchpoint {
+                    watchpoint: watchpoint.clone(),
+                    old,
+                    new,
+                });
+            }
+        }
+
+        if self.vm.is_finished() {
+            return Ok(StopReason::Finished);
+        }
+        if self.breakpoints.contains(&self.vm.pc()) {
+            return Ok(StopReason::Breakpoint(self.vm.pc()));
+        }
+        Ok(StopReason::Step)
+    }
+
+    /// Run until a breakpoint, a watchpoint or the end of execution.
+    ///
+    /// A breakpoint at the current PC does not stop the run, so calling this
+    /// again after a breakpoint continues past it.
+    pub fn resume(&mut self) -> Result<StopReason, VmError> {
+        loop {
+            match self.step()? {
+                StopReason::Step => continue,
+                reason => return Ok(reason),
+            }
+        }
+    }
+
+    /// Run to completion, ignoring breakpoints and watchpoints.
+    pub fn finish(&mut self) -> Result<ExecutionResult, VmError> {
+        self.vm.run()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::GasSchedule;
+    use minichain_core::Address;
+
+    fn loadi(reg: u8, value: u64) -> Vec<u8> {
+        let mut code = vec![0x70, reg << 4];
+        code.extend_from_slice(&value.to_le_bytes());
+        code
+    }
+
+    fn debugger(code: Vec<u8>) -> Debugger<'static> {
+        let vm = Vm::new(
+            code,
+            100_000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            GasSchedule::default(),
+        );
+        Debugger::new(vm)
+    }
+
+    #[test]
+    fn test_single_step() {
+        // LOADI R0, 7 / LOADI R1, 9 / HALT
+        let mut code = loadi(0, 7);
+        code.extend(loadi(1, 9));
+        code.push(0x00);
+        let mut dbg = debugger(code);
+
+        assert_eq!(dbg.step().unwrap(), StopReason::Step);
+        assert_eq!(dbg.vm().pc(), 10);
+        assert_eq!(dbg.vm().get_registers()[0], 7);
+        assert_eq!(dbg.vm().get_registers()[1], 0);
+
+        assert_eq!(dbg.step().unwrap(), StopReason::Step);
+        assert_eq!(dbg.step().unwrap(), StopReason::Finished);
+        assert_eq!(dbg.step().unwrap(), StopReason::Finished);
+        assert!(dbg.finish().unwrap().success);
+    }
+
+    #[test]
+    fn test_breakpoint_by_pc_and_label() {
+        let mut code = loadi(0, 1);
+        code.extend(loadi(1, 2));
+        code.extend(loadi(2, 3));
+        code.push(0x00);
+        let labels = HashMap::from([("third".to_string(), 20)]);
+        let mut dbg = debugger(code).with_labels(labels);
+
+        dbg.add_breakpoint(10);
+        assert_eq!(dbg.add_label_breakpoint("third"), Some(20));
+        assert_eq!(dbg.add_label_breakpoint("missing"), None);
+        assert_eq!(dbg.label_at(20), Some("third"));
+
+        assert_eq!(dbg.resume().unwrap(), StopReason::Breakpoint(10));
+        assert_eq!(dbg.resume().unwrap(), StopReason::Breakpoint(20));
+        assert_eq!(dbg.vm().get_registers()[1], 2);
+        assert_eq!(dbg.vm().get_registers()[2], 0);
+        assert_eq!(dbg.resume().unwrap(), StopReason::Finished);
+    }
+
+    #[test]
+    fn test_register_and_memory_watchpoints() {
+        // LOADI R0, 5 / LOADI R1, 64 / STORE64 R1, R0 / HALT
+        let mut code = loadi(0, 5);
+        code.extend(loadi(1, 64));
+        code.extend_from_slice(&[0x43, 0x10]);
+        code.push(0x00);
+        let mut dbg = debugger(code);
+
+        dbg.add_watchpoint(Watchpoint::Memory { offset: 64, len: 8 });
+        dbg.add_watchpoint(Watchpoint::Register(1));
+
+        match dbg.resume().unwrap() {
+            StopReason::Watchpoint {
+                watchpoint,
+                old,
+                new,
+            } => {
+                assert_eq!(watchpoint, Watchpoint::Register(1));
+                assert_eq!(old, 0u64.to_be_bytes());
+                assert_eq!(new, 64u64.to_be_bytes());
+            }
+            other => panic!("unexpected stop: {other:?}"),
+        }
+
+        match dbg.resume().unwrap() {
+            StopReason::Watchpoint {
+                watchpoint, new, ..
+            } => {
+                assert_eq!(watchpoint, Watchpoint::Memory { offset: 64, len: 8 });
+                assert_eq!(new, 5u64.to_le_bytes());
+            }
+            other => panic!("unexpected stop: {other:?}"),
+        }
+        assert_eq!(dbg.resume().unwrap(), StopReason::Finished);
+    }
+
+    #[test]
+    fn test_memory_watchpoint_clamped_to_max_memory() {
+        let mut dbg = debugger(vec![0x00]);
+        let max_size = dbg.vm().memory().max_size();
+        let watchpoint = Watchpoint::Memory {
+            offset: max_size as u32 - 4,
+            len: u32::MAX,
+        };
+
+        assert_eq!(dbg.watch_value(&watchpoint), vec![0; 4]);
+        dbg.add_watchpoint(watchpoint);
+        assert_eq!(dbg.resume().unwrap(), StopReason::Finished);
+    }
+}
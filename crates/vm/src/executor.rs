@@ -1,13 +1,33 @@
 //! VM execution loop.
 
 use crate::{
-    gas::{GasCosts, GasMeter},
+    context::{BlockContext, TxContext},
+    gas::{GasMeter, GasSchedule},
+    host::Host,
+    inspector::Inspector,
     memory::{Memory, Registers},
     opcodes::Opcode,
+    program::{Instruction, Program},
+    syscall::HostFunctions,
+    verify::verify,
 };
-use minichain_core::Address;
+use minichain_core::{hash, Address, Hash, Log, PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::Arc;
 use thiserror::Error;
 
+/// Maximum nesting of inter-contract calls.
+pub const MAX_CALL_DEPTH: usize = 64;
+
+/// Default upper bound on the bytes a single RETURN may hand back.
+pub const DEFAULT_MAX_RETURN_SIZE: usize = 4096;
+
+/// Default number of JSUB return addresses a frame may hold.
+pub const DEFAULT_MAX_RETURN_DEPTH: usize = 1024;
+
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum VmError {
     #[error("Out of gas: required {required}, remaining {remaining}")]
@@ -19,6 +39,9 @@ pub enum VmError {
     #[error("Division by zero")]
     DivisionByZero,
 
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow,
+
     #[error("Memory overflow")]
     MemoryOverflow,
 
@@ -28,16 +51,96 @@ pub enum VmError {
     #[error("Stack underflow")]
     StackUnderflow,
 
+    #[error("Stack overflow: more than {max} nested subroutine calls")]
+    StackOverflow { max: usize },
+
     #[error("Execution reverted")]
-    Reverted,
+    Reverted(Vec<u8>),
+
+    #[error("Return data too large: {size} bytes (max {max})")]
+    ReturnDataTooLarge { size: usize, max: usize },
+
+    #[error("Transfer of {amount} failed")]
+    TransferFailed { amount: u64 },
+
+    #[error("{0:?} is not allowed in static mode")]
+    StaticViolation(Opcode),
+
+    #[error("Unknown syscall: {0}")]
+    UnknownSyscall(u64),
+
+    #[error("Syscall {id} failed: {reason}")]
+    SyscallFailed { id: u64, reason: String },
+}
+
+/// How an execution ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum ExecutionStatus {
+    /// Stopped at HALT.
+    Halted,
+    /// Stopped at RETURN.
+    Returned,
+    /// REVERT, with the data it passed back.
+    Reverted(#[serde(with = "hex_bytes")] Vec<u8>),
+    OutOfGas,
+    /// Executed an unknown opcode byte.
+    InvalidOpcode(u8),
+    /// Jumped to an offset that does not start an instruction.
+    InvalidJump(usize),
+    MemoryOverflow,
+    /// Tried to change state in static mode.
+    StaticViolation,
+    /// Ran past the end of the code without HALT or RETURN.
+    FellOffEnd,
+    /// Any other VM error, with its message.
+    Failed(String),
+}
+
+impl ExecutionStatus {
+    /// Whether the execution completed and its state changes may be kept.
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Halted | Self::Returned)
+    }
+}
+
+impl From<VmError> for ExecutionStatus {
+    fn from(err: VmError) -> Self {
+        match err {
+            VmError::OutOfGas { .. } => Self::OutOfGas,
+            VmError::InvalidOpcode(byte) => Self::InvalidOpcode(byte),
+            VmError::InvalidJump(addr) => Self::InvalidJump(addr),
+            VmError::MemoryOverflow => Self::MemoryOverflow,
+            VmError::Reverted(data) => Self::Reverted(data),
+            VmError::StaticViolation(_) => Self::StaticViolation,
+            other => Self::Failed(other.to_string()),
+        }
+    }
+}
+
+/// Hex (de)serialization for revert payloads.
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        hex::decode(text).map_err(serde::de::Error::custom)
+    }
 }
 
 /// Execution result.
 pub struct ExecutionResult {
+    /// Set when `status` is a success.
     pub success: bool,
+    pub status: ExecutionStatus,
     pub gas_used: u64,
     pub return_data: Vec<u8>,
-    pub logs: Vec<u64>, // LOG opcode outputs
+    pub logs: Vec<u64>,   // LOG opcode outputs
+    pub events: Vec<Log>, // LOG0-LOG4 outputs
 }
 
 /// The virtual machine state.
@@ -46,73 +149,138 @@ pub struct Vm<'a> {
     memory: Memory,
     pc: usize,
     gas: GasMeter,
-    bytecode: Vec<u8>,
+    schedule: GasSchedule,
+    program: Arc<Program>,
+    instruction: Instruction,
+    calldata: Vec<u8>,
     halted: bool,
 
+    // JSUB return addresses
+    return_stack: Vec<usize>,
+    max_return_depth: usize,
+
     // Context
     caller: Address,
     address: Address,
     call_value: u64,
-    block_number: u64,
-    timestamp: u64,
+    block: BlockContext,
+    tx: TxContext,
 
     // Storage backend
     storage: Option<Box<dyn StorageBackend + 'a>>,
+    storage_writes: BTreeMap<[u8; 32], [u8; 32]>,
+
+    // World-state host for inter-contract calls
+    host: Option<Rc<dyn Host + 'a>>,
+    host_functions: Option<Rc<dyn HostFunctions + 'a>>,
+    depth: usize,
+    is_static: bool,
+
+    // Execution hooks, shared with child frames
+    inspector: Option<Rc<RefCell<dyn Inspector + 'a>>>,
 
     // Outputs
     logs: Vec<u64>,
+    events: Vec<Log>,
+    return_data: Option<Vec<u8>>,
+    max_return_size: usize,
+}
+
+/// Outcome of a nested call frame as seen by the caller.
+struct CallOutcome {
+    success: bool,
+    gas_left: u64,
+    return_data: Vec<u8>,
+}
+
+impl CallOutcome {
+    fn failure(gas_left: u64) -> Self {
+        Self {
+            success: false,
+            gas_left,
+            return_data: Vec::new(),
+        }
+    }
 }
 
 impl<'a> Vm<'a> {
-    /// Create a new VM with the given bytecode and gas limit.
+    /// Create a new VM with the given bytecode, gas limit and gas schedule.
     pub fn new(
         bytecode: Vec<u8>,
         gas_limit: u64,
         caller: Address,
         address: Address,
         call_value: u64,
+        schedule: GasSchedule,
     ) -> Self {
-        Self {
-            registers: Registers::new(),
-            memory: Memory::new(1024 * 1024), // 1MB max
-            pc: 0,
-            gas: GasMeter::new(gas_limit),
-            bytecode,
-            halted: false,
+        Self::from_program(
+            Arc::new(Program::new(bytecode)),
+            gas_limit,
             caller,
             address,
             call_value,
-            block_number: 0,
-            timestamp: 0,
-            storage: None,
-            logs: Vec::new(),
-        }
+            schedule,
+        )
     }
 
     /// Create a new VM with additional context fields.
+    #[allow(clippy::too_many_arguments)]
     pub fn new_with_context(
         bytecode: Vec<u8>,
         gas_limit: u64,
         caller: Address,
         address: Address,
         call_value: u64,
-        block_number: u64,
-        timestamp: u64,
+        block: BlockContext,
+        tx: TxContext,
+        schedule: GasSchedule,
+    ) -> Self {
+        let mut vm = Self::new(bytecode, gas_limit, caller, address, call_value, schedule);
+        vm.set_block_context(block);
+        vm.set_tx_context(tx);
+        vm
+    }
+
+    /// Create a new VM running an already decoded, possibly shared, program.
+    pub fn from_program(
+        program: Arc<Program>,
+        gas_limit: u64,
+        caller: Address,
+        address: Address,
+        call_value: u64,
+        schedule: GasSchedule,
     ) -> Self {
         Self {
             registers: Registers::new(),
-            memory: Memory::new(1024 * 1024),
+            memory: Memory::new(1024 * 1024), // 1MB max
             pc: 0,
             gas: GasMeter::new(gas_limit),
-            bytecode,
+            schedule,
+            program,
+            instruction: Instruction::default(),
             halted: false,
+            return_stack: Vec::new(),
+            max_return_depth: DEFAULT_MAX_RETURN_DEPTH,
             caller,
             address,
             call_value,
-            block_number,
-            timestamp,
+            block: BlockContext::default(),
+            tx: TxContext {
+                origin: caller,
+                gas_price: 0,
+            },
             storage: None,
+            storage_writes: BTreeMap::new(),
+            host: None,
+            host_functions: None,
+            calldata: Vec::new(),
+            depth: 0,
+            is_static: false,
+            inspector: None,
             logs: Vec::new(),
+            events: Vec::new(),
+            return_data: None,
+            max_return_size: DEFAULT_MAX_RETURN_SIZE,
         }
     }
 
@@ -121,15 +289,62 @@ impl<'a> Vm<'a> {
         self.storage = Some(storage);
     }
 
+    /// Set the host used to resolve inter-contract calls.
+    pub fn set_host(&mut self, host: Rc<dyn Host + 'a>) {
+        self.host = Some(host);
+    }
+
+    /// Set the functions SYSCALL dispatches to. Child frames inherit them.
+    pub fn set_host_functions(&mut self, host_functions: Rc<dyn HostFunctions + 'a>) {
+        self.host_functions = Some(host_functions);
+    }
+
+    /// Attach an inspector that observes this frame and every child frame.
+    pub fn set_inspector(&mut self, inspector: Rc<RefCell<dyn Inspector + 'a>>) {
+        self.inspector = Some(inspector);
+    }
+
+    /// Reject state changes: SSTORE, TSTORE, LOG, TRANSFER, CREATE and value-carrying CALLs
+    /// fail with [`VmError::StaticViolation`]. Child frames inherit the mode.
+    pub fn set_static(&mut self, is_static: bool) {
+        self.is_static = is_static;
+    }
+
+    /// Whether this frame runs in static mode.
+    pub fn is_static(&self) -> bool {
+        self.is_static
+    }
+
+    /// Set the maximum number of bytes RETURN may produce.
+    pub fn set_max_return_size(&mut self, max_return_size: usize) {
+        self.max_return_size = max_return_size;
+    }
+
+    /// Set how many JSUB return addresses may be outstanding at once.
+    pub fn set_max_return_depth(&mut self, max_return_depth: usize) {
+        self.max_return_depth = max_return_depth;
+    }
+
+    /// Set the read-only calldata exposed through the CALLDATA* instructions.
+    pub fn set_calldata(&mut self, calldata: Vec<u8>) {
+        self.calldata = calldata;
+    }
+
     /// Preload calldata or other input bytes into memory at the given offset.
     pub fn load_memory(&mut self, offset: u32, bytes: &[u8]) -> Result<(), VmError> {
         self.memory.store_bytes(offset, bytes)
     }
 
-    /// Set the block context.
-    pub fn set_block_context(&mut self, block_number: u64, timestamp: u64) {
-        self.block_number = block_number;
-        self.timestamp = timestamp;
+    /// Set the block the code executes in. Child frames inherit it.
+    pub fn set_block_context(&mut self, block: BlockContext) {
+        self.block = block;
+    }
+
+    /// Set the transaction the code executes in. Child frames inherit it.
+    ///
+    /// Without one, the origin is this frame's caller and the gas price is zero.
+    pub fn set_tx_context(&mut self, tx: TxContext) {
+        self.tx = tx;
     }
 
     /// Get current register values for tracing.
@@ -142,41 +357,155 @@ impl<'a> Vm<'a> {
         self.gas.remaining()
     }
 
+    /// Get gas used so far.
+    pub fn gas_used(&self) -> u64 {
+        self.gas.used()
+    }
+
+    /// Get the address of the executing contract.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Get the call depth; the top-level frame is 0.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Get the program counter.
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Get the opcode at the program counter, if execution has not finished.
+    pub fn current_opcode(&self) -> Option<Opcode> {
+        if self.is_finished() {
+            return None;
+        }
+        self.program.instruction_at(self.pc)?.opcode
+    }
+
+    /// Get the VM memory for inspection.
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    /// Storage slots written by this frame, with the last value written to each.
+    ///
+    /// These writes are still pending: the embedder commits them once the
+    /// transaction succeeds.
+    pub fn storage_writes(&self) -> &BTreeMap<[u8; 32], [u8; 32]> {
+        &self.storage_writes
+    }
+
+    /// Read a storage slot without charging gas.
+    pub fn peek_storage(&self, key: &[u8; 32]) -> [u8; 32] {
+        match &self.storage {
+            Some(storage) => storage.sload(key),
+            None => [0u8; 32],
+        }
+    }
+
+    /// Whether execution has halted or run off the end of the bytecode.
+    pub fn is_finished(&self) -> bool {
+        self.halted || self.pc >= self.program.len()
+    }
+
     /// Run the VM until it halts or runs out of gas.
     pub fn run(&mut self) -> Result<ExecutionResult, VmError> {
-        while !self.halted && self.pc < self.bytecode.len() {
+        while !self.is_finished() {
             self.step()?;
         }
+        Ok(self.finish())
+    }
 
-        let return_len = self.memory.size().min(8) as u32;
-        Ok(ExecutionResult {
-            success: self.halted, // HALT = success, out of bounds = failure
+    /// Collect the outputs of a finished execution.
+    ///
+    /// `run` calls this itself; callers driving the VM with [`Vm::step`]
+    /// call it once [`Vm::is_finished`] returns true.
+    pub fn finish(&mut self) -> ExecutionResult {
+        // Without an explicit RETURN, fall back to the first word of memory.
+        let explicit_return = self.return_data.is_some();
+        let return_data = match self.return_data.take() {
+            Some(data) => data,
+            None => {
+                let return_len = self.memory.size().min(8) as u32;
+                self.memory.read_range(0, return_len)
+            }
+        };
+        let status = match (self.halted, explicit_return) {
+            (true, true) => ExecutionStatus::Returned,
+            (true, false) => ExecutionStatus::Halted,
+            (false, _) => ExecutionStatus::FellOffEnd,
+        };
+        ExecutionResult {
+            success: status.is_success(),
+            status,
             gas_used: self.gas.used(),
-            return_data: self.memory.read_range(0, return_len),
+            return_data,
             logs: std::mem::take(&mut self.logs),
-        })
+            events: std::mem::take(&mut self.events),
+        }
     }
 
-    /// Execute a single instruction.
-    fn step(&mut self) -> Result<(), VmError> {
+    /// Execute a single instruction. Does nothing once execution has finished.
+    pub fn step(&mut self) -> Result<(), VmError> {
+        if self.is_finished() {
+            return Ok(());
+        }
+
+        self.inspect(|inspector, vm| inspector.step(vm));
+        let result = self.execute_instruction();
+        match &result {
+            Ok(()) => self.inspect(|inspector, vm| inspector.step_end(vm)),
+            Err(err) => {
+                if let VmError::Reverted(data) = err {
+                    self.inspect(|inspector, vm| inspector.revert(vm, data));
+                }
+                self.inspect(|inspector, vm| inspector.step_error(vm, err));
+            }
+        }
+        result
+    }
+
+    /// Run an inspector callback, if an inspector is attached.
+    fn inspect(&self, f: impl FnOnce(&mut dyn Inspector, &Self)) {
+        if let Some(inspector) = &self.inspector {
+            f(&mut *inspector.borrow_mut(), self);
+        }
+    }
+
+    fn execute_instruction(&mut self) -> Result<(), VmError> {
         // Fetch
-        let opcode_byte = self.bytecode[self.pc];
-        let opcode = Opcode::from_byte(opcode_byte).ok_or(VmError::InvalidOpcode(opcode_byte))?;
+        // Jumps are checked against the jump table and every other
+        // instruction advances by its own size, so this only fails if a
+        // handler's PC increment disagrees with `instruction_size`.
+        self.instruction = *self
+            .program
+            .instruction_at(self.pc)
+            .ok_or(VmError::InvalidJump(self.pc))?;
+        let opcode = self
+            .instruction
+            .opcode
+            .ok_or(VmError::InvalidOpcode(self.instruction.byte))?;
+        if self.is_static && opcode.modifies_state() {
+            return Err(VmError::StaticViolation(opcode));
+        }
+        // Handlers charge only the parts of the cost that depend on operands.
+        self.gas.consume(self.schedule.cost(opcode))?;
+        let memory_size = self.memory.size();
 
         // Decode & Execute
         match opcode {
             Opcode::HALT => {
-                self.gas.consume(GasCosts::ZERO)?;
                 self.halted = true;
             }
 
             Opcode::NOP => {
-                self.gas.consume(GasCosts::ZERO)?;
                 self.pc += 1;
             }
 
             Opcode::ADD => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 let result = self.registers.get(s1).wrapping_add(self.registers.get(s2));
                 self.registers.set(dst, result);
@@ -184,7 +513,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::SUB => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 let result = self.registers.get(s1).wrapping_sub(self.registers.get(s2));
                 self.registers.set(dst, result);
@@ -192,7 +520,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::MUL => {
-                self.gas.consume(GasCosts::LOW)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 let result = self.registers.get(s1).wrapping_mul(self.registers.get(s2));
                 self.registers.set(dst, result);
@@ -200,7 +527,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::DIV => {
-                self.gas.consume(GasCosts::MID)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 let divisor = self.registers.get(s2);
                 if divisor == 0 {
@@ -211,7 +537,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::MOD => {
-                self.gas.consume(GasCosts::MID)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 let divisor = self.registers.get(s2);
                 if divisor == 0 {
@@ -221,17 +546,77 @@ impl<'a> Vm<'a> {
                 self.pc += 3;
             }
 
+            Opcode::SDIV => {
+                let (dst, s1, s2) = self.decode_rrr();
+                let divisor = self.registers.get(s2) as i64;
+                if divisor == 0 {
+                    return Err(VmError::DivisionByZero);
+                }
+                let result = (self.registers.get(s1) as i64).wrapping_div(divisor);
+                self.registers.set(dst, result as u64);
+                self.pc += 3;
+            }
+
+            Opcode::SMOD => {
+                let (dst, s1, s2) = self.decode_rrr();
+                let divisor = self.registers.get(s2) as i64;
+                if divisor == 0 {
+                    return Err(VmError::DivisionByZero);
+                }
+                let result = (self.registers.get(s1) as i64).wrapping_rem(divisor);
+                self.registers.set(dst, result as u64);
+                self.pc += 3;
+            }
+
+            Opcode::ADDC => {
+                let (dst, s1, s2) = self.decode_rrr();
+                let result = self
+                    .registers
+                    .get(s1)
+                    .checked_add(self.registers.get(s2))
+                    .ok_or(VmError::ArithmeticOverflow)?;
+                self.registers.set(dst, result);
+                self.pc += 3;
+            }
+
+            Opcode::SUBC => {
+                let (dst, s1, s2) = self.decode_rrr();
+                let result = self
+                    .registers
+                    .get(s1)
+                    .checked_sub(self.registers.get(s2))
+                    .ok_or(VmError::ArithmeticOverflow)?;
+                self.registers.set(dst, result);
+                self.pc += 3;
+            }
+
+            Opcode::MULC => {
+                let (dst, s1, s2) = self.decode_rrr();
+                let result = self
+                    .registers
+                    .get(s1)
+                    .checked_mul(self.registers.get(s2))
+                    .ok_or(VmError::ArithmeticOverflow)?;
+                self.registers.set(dst, result);
+                self.pc += 3;
+            }
+
+            Opcode::MULHI => {
+                let (dst, s1, s2) = self.decode_rrr();
+                let product = self.registers.get(s1) as u128 * self.registers.get(s2) as u128;
+                self.registers.set(dst, (product >> 64) as u64);
+                self.pc += 3;
+            }
+
             Opcode::ADDI => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, src) = self.decode_rr();
-                let immediate = self.decode_imm32() as u64;
+                let immediate = self.decode_imm64();
                 let result = self.registers.get(src).wrapping_add(immediate);
                 self.registers.set(dst, result);
-                self.pc += 6;
+                self.pc += 10;
             }
 
             Opcode::AND => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 self.registers
                     .set(dst, self.registers.get(s1) & self.registers.get(s2));
@@ -239,7 +624,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::OR => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 self.registers
                     .set(dst, self.registers.get(s1) | self.registers.get(s2));
@@ -247,7 +631,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::XOR => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 self.registers
                     .set(dst, self.registers.get(s1) ^ self.registers.get(s2));
@@ -255,14 +638,12 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::NOT => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
                 self.registers.set(dst, !self.registers.get(dst));
                 self.pc += 2;
             }
 
             Opcode::SHL => {
-                self.gas.consume(GasCosts::MID)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 let shift = self.registers.get(s2) & 0x3F;
                 self.registers.set(dst, self.registers.get(s1) << shift);
@@ -270,15 +651,21 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::SHR => {
-                self.gas.consume(GasCosts::MID)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 let shift = self.registers.get(s2) & 0x3F;
                 self.registers.set(dst, self.registers.get(s1) >> shift);
                 self.pc += 3;
             }
 
+            Opcode::SAR => {
+                let (dst, s1, s2) = self.decode_rrr();
+                let shift = self.registers.get(s2) & 0x3F;
+                self.registers
+                    .set(dst, ((self.registers.get(s1) as i64) >> shift) as u64);
+                self.pc += 3;
+            }
+
             Opcode::EQ => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 self.registers.set(
                     dst,
@@ -288,7 +675,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::NE => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 self.registers.set(
                     dst,
@@ -298,7 +684,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::LT => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 self.registers.set(
                     dst,
@@ -308,7 +693,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::GT => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 self.registers.set(
                     dst,
@@ -318,7 +702,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::LE => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 self.registers.set(
                     dst,
@@ -328,7 +711,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::GE => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, s1, s2) = self.decode_rrr();
                 self.registers.set(
                     dst,
@@ -338,15 +720,31 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::ISZERO => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
                 self.registers
                     .set(dst, (self.registers.get(dst) == 0) as u64);
                 self.pc += 2;
             }
 
+            Opcode::SLT => {
+                let (dst, s1, s2) = self.decode_rrr();
+                self.registers.set(
+                    dst,
+                    ((self.registers.get(s1) as i64) < (self.registers.get(s2) as i64)) as u64,
+                );
+                self.pc += 3;
+            }
+
+            Opcode::SGT => {
+                let (dst, s1, s2) = self.decode_rrr();
+                self.registers.set(
+                    dst,
+                    ((self.registers.get(s1) as i64) > (self.registers.get(s2) as i64)) as u64,
+                );
+                self.pc += 3;
+            }
+
             Opcode::LOADI => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
                 let immediate = self.decode_imm64();
                 self.registers.set(dst, immediate);
@@ -354,28 +752,25 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::MOV => {
-                self.gas.consume(GasCosts::BASE)?;
                 let (dst, src) = self.decode_rr();
                 self.registers.set(dst, self.registers.get(src));
                 self.pc += 2;
             }
 
             Opcode::JUMP => {
-                self.gas.consume(GasCosts::JUMP)?;
                 let target = self.decode_r();
                 let addr = self.registers.get(target) as usize;
-                if addr >= self.bytecode.len() {
+                if !self.program.is_jump_target(addr) {
                     return Err(VmError::InvalidJump(addr));
                 }
                 self.pc = addr;
             }
 
             Opcode::JUMPI => {
-                self.gas.consume(GasCosts::JUMP)?;
                 let (cond, target) = self.decode_rr();
                 if self.registers.get(cond) != 0 {
                     let addr = self.registers.get(target) as usize;
-                    if addr >= self.bytecode.len() {
+                    if !self.program.is_jump_target(addr) {
                         return Err(VmError::InvalidJump(addr));
                     }
                     self.pc = addr;
@@ -384,33 +779,60 @@ impl<'a> Vm<'a> {
                 }
             }
 
+            // Halts like HALT; subroutine returns use JRET.
             Opcode::RET => {
-                self.gas.consume(GasCosts::ZERO)?;
                 self.halted = true;
                 self.pc += 1;
             }
 
+            Opcode::JSUB => {
+                let target = self.decode_r();
+                self.jump_to_subroutine(self.registers.get(target) as usize, 2)?;
+            }
+            Opcode::JSUBI => {
+                self.jump_to_subroutine(self.decode_imm64() as usize, 9)?;
+            }
+
+            Opcode::JRET => {
+                self.pc = self.return_stack.pop().ok_or(VmError::StackUnderflow)?;
+            }
+
+            Opcode::RETURN => {
+                self.execute_return()?;
+            }
+
             Opcode::REVERT => {
-                self.gas.consume(GasCosts::ZERO)?;
                 self.halted = true;
                 self.pc += 1;
-                return Err(VmError::Reverted);
+                return Err(VmError::Reverted(Vec::new()));
+            }
+
+            Opcode::REVERTD => {
+                let data = self.read_output_range()?;
+                self.halted = true;
+                self.pc += 2;
+                return Err(VmError::Reverted(data));
             }
 
             Opcode::CALL => {
-                self.gas.consume(GasCosts::CALL)?;
-                self.pc += 1;
+                self.execute_call()?;
             }
 
             Opcode::LOG => {
-                self.gas.consume(GasCosts::BASE)?;
                 let src = self.decode_r();
-                self.logs.push(self.registers.get(src));
+                let value = self.registers.get(src);
+                self.inspect(|inspector, vm| inspector.log(vm, value));
+                self.logs.push(value);
                 self.pc += 2;
             }
 
+            Opcode::LOG0 => self.execute_log(0)?,
+            Opcode::LOG1 => self.execute_log(1)?,
+            Opcode::LOG2 => self.execute_log(2)?,
+            Opcode::LOG3 => self.execute_log(3)?,
+            Opcode::LOG4 => self.execute_log(4)?,
+
             Opcode::LOAD8 => {
-                self.gas.consume(GasCosts::MEMORY_READ)?;
                 let (dst, addr_reg) = self.decode_rr();
                 let addr = self.registers.get(addr_reg) as u32;
                 let value = self.memory.load8(addr) as u64;
@@ -419,7 +841,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::LOAD64 => {
-                self.gas.consume(GasCosts::MEMORY_READ)?;
                 let (dst, addr_reg) = self.decode_rr();
                 let addr = self.registers.get(addr_reg) as u32;
                 let value = self.memory.load64(addr);
@@ -428,7 +849,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::STORE8 => {
-                self.gas.consume(GasCosts::MEMORY_WRITE)?;
                 let (addr_reg, value_reg) = self.decode_rr();
                 let addr = self.registers.get(addr_reg) as u32;
                 let value = self.registers.get(value_reg) as u8;
@@ -437,7 +857,6 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::STORE64 => {
-                self.gas.consume(GasCosts::MEMORY_WRITE)?;
                 let (addr_reg, value_reg) = self.decode_rr();
                 let addr = self.registers.get(addr_reg) as u32;
                 let value = self.registers.get(value_reg);
@@ -446,14 +865,12 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::MSIZE => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
                 self.registers.set(dst, self.memory.size() as u64);
                 self.pc += 2;
             }
 
             Opcode::MCOPY => {
-                self.gas.consume(GasCosts::MEMORY_READ)?;
                 let (dst_reg, src_reg, len_reg) = self.decode_rrr();
                 let dest = self.registers.get(dst_reg) as u32;
                 let src = self.registers.get(src_reg) as u32;
@@ -462,6 +879,44 @@ impl<'a> Vm<'a> {
                 self.pc += 3;
             }
 
+            Opcode::CALLDATASIZE => {
+                let dst = self.decode_r();
+                self.registers.set(dst, self.calldata.len() as u64);
+                self.pc += 2;
+            }
+
+            Opcode::CALLDATALOAD => {
+                let (dst, off_reg) = self.decode_rr();
+                let mut word = [0u8; 8];
+                let bytes = self.calldata_range(self.registers.get(off_reg), 8);
+                word[..bytes.len()].copy_from_slice(bytes);
+                self.registers.set(dst, u64::from_le_bytes(word));
+                self.pc += 2;
+            }
+
+            Opcode::CALLDATACOPY => self.execute_calldatacopy()?,
+
+            Opcode::HASH => {
+                let (dst_off_reg, off_reg, len_reg) = self.decode_rrr();
+                let digest = self.hash_memory(off_reg, len_reg)?;
+                let dst_off = self.registers.get(dst_off_reg) as u32;
+                self.memory.store_bytes(dst_off, digest.as_bytes())?;
+                self.pc += 3;
+            }
+
+            Opcode::HASH64 => {
+                let (dst, off_reg, len_reg) = self.decode_rrr();
+                let digest = self.hash_memory(off_reg, len_reg)?;
+                // Low 64 bits of the digest read as a big-endian word.
+                let low = u64::from_be_bytes(digest.0[24..32].try_into().unwrap());
+                self.registers.set(dst, low);
+                self.pc += 3;
+            }
+
+            Opcode::SIGVERIFY => {
+                self.execute_sigverify()?;
+            }
+
             Opcode::SLOAD => {
                 self.execute_sload()?;
             }
@@ -470,8 +925,37 @@ impl<'a> Vm<'a> {
                 self.execute_sstore_internal()?;
             }
 
+            Opcode::SLOADM => {
+                self.execute_sloadm()?;
+            }
+
+            Opcode::SSTOREM => {
+                self.execute_sstorem()?;
+            }
+
+            Opcode::TLOAD => {
+                let (dst, key_reg) = self.decode_rr();
+                let key = word_from_u64(self.registers.get(key_reg));
+                let value = self
+                    .storage
+                    .as_ref()
+                    .map_or([0u8; 32], |storage| storage.tload(&key));
+                self.registers
+                    .set(dst, u64::from_be_bytes(value[24..32].try_into().unwrap()));
+                self.pc += 2;
+            }
+
+            Opcode::TSTORE => {
+                let (key_reg, value_reg) = self.decode_rr();
+                let key = word_from_u64(self.registers.get(key_reg));
+                let value = word_from_u64(self.registers.get(value_reg));
+                if let Some(storage) = &mut self.storage {
+                    storage.tstore(&key, &value);
+                }
+                self.pc += 2;
+            }
+
             Opcode::CALLER => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
                 let caller_bytes = self.caller.as_bytes();
                 let mut value = [0u8; 8];
@@ -481,14 +965,12 @@ impl<'a> Vm<'a> {
             }
 
             Opcode::CALLVALUE => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
                 self.registers.set(dst, self.call_value);
                 self.pc += 2;
             }
 
             Opcode::ADDRESS => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
                 let address_bytes = self.address.as_bytes();
                 let mut value = [0u8; 8];
@@ -497,66 +979,170 @@ impl<'a> Vm<'a> {
                 self.pc += 2;
             }
 
+            Opcode::CALLERM => {
+                let off_reg = self.decode_r();
+                let offset = self.registers.get(off_reg) as u32;
+                self.memory.store_bytes(offset, self.caller.as_bytes())?;
+                self.pc += 2;
+            }
+
+            Opcode::ADDRESSM => {
+                let off_reg = self.decode_r();
+                let offset = self.registers.get(off_reg) as u32;
+                self.memory.store_bytes(offset, self.address.as_bytes())?;
+                self.pc += 2;
+            }
+
+            Opcode::ADDREQ => {
+                let (dst, a_reg, b_reg) = self.decode_rrr();
+                let a = self.read_address(self.registers.get(a_reg) as u32);
+                let b = self.read_address(self.registers.get(b_reg) as u32);
+                self.registers.set(dst, (a == b) as u64);
+                self.pc += 3;
+            }
+
+            Opcode::BALANCE => {
+                let (dst, addr_reg) = self.decode_rr();
+                let address = self.read_address(self.registers.get(addr_reg) as u32);
+                let balance = self.host.as_ref().map_or(0, |host| host.balance(&address));
+                self.registers.set(dst, balance);
+                self.pc += 2;
+            }
+
+            Opcode::SELFBALANCE => {
+                let dst = self.decode_r();
+                let balance = self
+                    .host
+                    .as_ref()
+                    .map_or(0, |host| host.balance(&self.address));
+                self.registers.set(dst, balance);
+                self.pc += 2;
+            }
+
+            Opcode::TRANSFER => {
+                self.execute_transfer()?;
+            }
+
+            Opcode::CREATE => {
+                self.execute_create()?;
+            }
+
+            Opcode::SYSCALL => {
+                self.execute_syscall()?;
+            }
+
             Opcode::BLOCKNUMBER => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
-                self.registers.set(dst, self.block_number);
+                self.registers.set(dst, self.block.number);
                 self.pc += 2;
             }
 
             Opcode::TIMESTAMP => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
-                self.registers.set(dst, self.timestamp);
+                self.registers.set(dst, self.block.timestamp);
+                self.pc += 2;
+            }
+
+            Opcode::CHAINID => {
+                let dst = self.decode_r();
+                self.registers.set(dst, self.block.chain_id);
+                self.pc += 2;
+            }
+
+            Opcode::ORIGIN => {
+                let off_reg = self.decode_r();
+                let offset = self.registers.get(off_reg) as u32;
+                self.memory.store_bytes(offset, self.tx.origin.as_bytes())?;
+                self.pc += 2;
+            }
+
+            Opcode::GASPRICE => {
+                let dst = self.decode_r();
+                self.registers.set(dst, self.tx.gas_price);
+                self.pc += 2;
+            }
+
+            Opcode::COINBASE => {
+                let off_reg = self.decode_r();
+                let offset = self.registers.get(off_reg) as u32;
+                self.memory
+                    .store_bytes(offset, self.block.author.as_bytes())?;
+                self.pc += 2;
+            }
+
+            Opcode::BLOCKHASH => {
+                let (off_reg, number_reg) = self.decode_rr();
+                let number = self.registers.get(number_reg);
+                // Blocks outside the window read as the zero hash.
+                let digest = self.block.block_hash(number).unwrap_or(Hash::ZERO);
+                let offset = self.registers.get(off_reg) as u32;
+                self.memory.store_bytes(offset, digest.as_bytes())?;
                 self.pc += 2;
             }
 
             Opcode::GAS => {
-                self.gas.consume(GasCosts::BASE)?;
                 let dst = self.decode_r();
                 self.registers.set(dst, self.gas.remaining());
                 self.pc += 2;
             }
         }
 
+        // Charge for any memory the instruction touched beyond the old size.
+        let expansion = self
+            .schedule
+            .memory_expansion_cost(memory_size, self.memory.size());
+        self.gas.consume(expansion)?;
+
+        Ok(())
+    }
+
+    /// Push the address of the next instruction on the return stack and jump to `addr`.
+    ///
+    /// `size` is the size of the calling JSUB or JSUBI instruction.
+    fn jump_to_subroutine(&mut self, addr: usize, size: usize) -> Result<(), VmError> {
+        if !self.program.is_jump_target(addr) {
+            return Err(VmError::InvalidJump(addr));
+        }
+        if self.return_stack.len() >= self.max_return_depth {
+            return Err(VmError::StackOverflow {
+                max: self.max_return_depth,
+            });
+        }
+        self.return_stack.push(self.pc + size);
+        self.pc = addr;
         Ok(())
     }
 
     /// Decode a single register operand: [opcode, RRRR____]
     fn decode_r(&self) -> usize {
-        ((self.bytecode[self.pc + 1] >> 4) & 0x0F) as usize
+        self.instruction.regs[0] as usize
     }
 
     /// Decode two register operands: [opcode, RRRR_SSSS]
     fn decode_rr(&self) -> (usize, usize) {
-        let byte = self.bytecode[self.pc + 1];
-        let r1 = ((byte >> 4) & 0x0F) as usize;
-        let r2 = (byte & 0x0F) as usize;
-        (r1, r2)
+        let regs = &self.instruction.regs;
+        (regs[0] as usize, regs[1] as usize)
     }
 
     /// Decode three register operands: [opcode, DDDD_SSS1, SSS2____]
     fn decode_rrr(&self) -> (usize, usize, usize) {
-        let b1 = self.bytecode[self.pc + 1];
-        let b2 = self.bytecode[self.pc + 2];
-        let dst = ((b1 >> 4) & 0x0F) as usize;
-        let s1 = (b1 & 0x0F) as usize;
-        let s2 = ((b2 >> 4) & 0x0F) as usize;
-        (dst, s1, s2)
+        let regs = &self.instruction.regs;
+        (regs[0] as usize, regs[1] as usize, regs[2] as usize)
     }
 
-    /// Decode a 64-bit immediate value (little-endian).
-    fn decode_imm64(&self) -> u64 {
-        let start = self.pc + 2;
-        let bytes = &self.bytecode[start..start + 8];
-        u64::from_le_bytes(bytes.try_into().unwrap())
+    /// Decode `N` register operands packed two per byte after the opcode.
+    fn decode_regs<const N: usize>(&self) -> [usize; N] {
+        std::array::from_fn(|i| self.decode_reg_at(i))
+    }
+
+    /// Decode the `index`-th register operand packed two per byte after the opcode.
+    fn decode_reg_at(&self, index: usize) -> usize {
+        self.instruction.regs[index] as usize
     }
 
-    /// Decode a 32-bit immediate value (little-endian).
-    fn decode_imm32(&self) -> u32 {
-        let start = self.pc + 2;
-        let bytes = &self.bytecode[start..start + 4];
-        u32::from_le_bytes(bytes.try_into().unwrap())
+    /// Decode a 64-bit immediate value (little-endian).
+    fn decode_imm64(&self) -> u64 {
+        self.instruction.imm
     }
 }
 
@@ -567,25 +1153,24 @@ pub trait StorageBackend {
 
     /// Write 32 bytes to storage slot.
     fn sstore(&mut self, key: &[u8; 32], value: &[u8; 32]);
+
+    /// Read 32 bytes from a transient slot.
+    fn tload(&self, key: &[u8; 32]) -> [u8; 32];
+
+    /// Write 32 bytes to a transient slot.
+    ///
+    /// Transient slots are shared by every frame of the same contract within
+    /// a transaction and are discarded when the transaction ends.
+    fn tstore(&mut self, key: &[u8; 32], value: &[u8; 32]);
 }
 
 impl Vm<'_> {
     /// Execute SLOAD: read from persistent storage.
     fn execute_sload(&mut self) -> Result<(), VmError> {
-        self.gas.consume(GasCosts::SLOAD)?;
-
         let (dst, key_reg) = self.decode_rr();
+        let key = word_from_u64(self.registers.get(key_reg));
 
-        let key_value = self.registers.get(key_reg);
-        let mut key = [0u8; 32];
-        key[24..32].copy_from_slice(&key_value.to_be_bytes());
-
-        let value = if let Some(storage) = &self.storage {
-            storage.sload(&key)
-        } else {
-            [0u8; 32]
-        };
-
+        let value = self.sload_word(&key)?;
         self.registers
             .set(dst, u64::from_be_bytes(value[24..32].try_into().unwrap()));
 
@@ -596,32 +1181,454 @@ impl Vm<'_> {
     /// Execute SSTORE: write to persistent storage.
     fn execute_sstore_internal(&mut self) -> Result<(), VmError> {
         let (key_reg, value_reg) = self.decode_rr();
+        let key = word_from_u64(self.registers.get(key_reg));
+        let value = word_from_u64(self.registers.get(value_reg));
 
-        let key_value = self.registers.get(key_reg);
-        let mut key = [0u8; 32];
-        key[24..32].copy_from_slice(&key_value.to_be_bytes());
+        self.sstore_word(&key, &value)?;
 
-        let cost = if let Some(storage) = &self.storage {
-            let current = storage.sload(&key);
-            let is_empty = current == [0u8; 32];
-            if is_empty {
-                GasCosts::SSTORE_SET
-            } else {
-                GasCosts::SSTORE_RESET
-            }
+        self.pc += 2;
+        Ok(())
+    }
+
+    /// Execute SLOADM: copy the 32-byte word stored under a memory key into memory.
+    fn execute_sloadm(&mut self) -> Result<(), VmError> {
+        let (dst_off_reg, key_off_reg) = self.decode_rr();
+        let key = self.read_word(self.registers.get(key_off_reg) as u32);
+
+        let value = self.sload_word(&key)?;
+        self.memory
+            .store_bytes(self.registers.get(dst_off_reg) as u32, &value)?;
+
+        self.pc += 2;
+        Ok(())
+    }
+
+    /// Execute SSTOREM: store a 32-byte word from memory under a 32-byte memory key.
+    fn execute_sstorem(&mut self) -> Result<(), VmError> {
+        let (key_off_reg, val_off_reg) = self.decode_rr();
+        let key = self.read_word(self.registers.get(key_off_reg) as u32);
+        let value = self.read_word(self.registers.get(val_off_reg) as u32);
+
+        self.sstore_word(&key, &value)?;
+
+        self.pc += 2;
+        Ok(())
+    }
+
+    /// Read a full storage word.
+    fn sload_word(&mut self, key: &[u8; 32]) -> Result<[u8; 32], VmError> {
+        let value = self.peek_storage(key);
+        self.inspect(|inspector, vm| inspector.sload(vm, key, &value));
+        Ok(value)
+    }
+
+    /// Charge SSTORE gas (set vs. reset) and write a full storage word.
+    fn sstore_word(&mut self, key: &[u8; 32], value: &[u8; 32]) -> Result<(), VmError> {
+        let current = self.peek_storage(key);
+        let cost = if current == [0u8; 32] {
+            self.schedule.sstore_set
         } else {
-            GasCosts::SSTORE_SET
+            self.schedule.sstore_reset
         };
         self.gas.consume(cost)?;
 
-        let mut value = [0u8; 32];
-        value[24..32].copy_from_slice(&self.registers.get(value_reg).to_be_bytes());
-
         if let Some(storage) = &mut self.storage {
-            storage.sstore(&key, &value);
+            storage.sstore(key, value);
         }
+        self.inspect(|inspector, vm| inspector.sstore(vm, key, &current, value));
+        self.storage_writes.insert(*key, *value);
+        Ok(())
+    }
 
+    /// Read a 32-byte word from memory; bytes past the end read as zero.
+    fn read_word(&self, offset: u32) -> [u8; 32] {
+        let bytes = self.memory.read_range(offset, 32);
+        let mut word = [0u8; 32];
+        word[..bytes.len()].copy_from_slice(&bytes);
+        word
+    }
+}
+
+/// Widen a register value to a big-endian storage word.
+fn word_from_u64(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..32].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+impl Vm<'_> {
+    /// Execute RETURN: halt successfully with `len` bytes of memory from `offset`.
+    ///
+    /// Bytes past the end of memory read as zero.
+    fn execute_return(&mut self) -> Result<(), VmError> {
+        let data = self.read_output_range()?;
+        self.return_data = Some(data);
+        self.halted = true;
         self.pc += 2;
         Ok(())
     }
+
+    /// Read the `offset, len` memory range named by a RETURN or REVERT operand,
+    /// enforcing the return size limit.
+    fn read_output_range(&mut self) -> Result<Vec<u8>, VmError> {
+        let (offset_reg, len_reg) = self.decode_rr();
+        let offset = self.registers.get(offset_reg);
+        let len = self.registers.get(len_reg);
+        if len > self.max_return_size as u64 {
+            return Err(VmError::ReturnDataTooLarge {
+                size: len.min(usize::MAX as u64) as usize,
+                max: self.max_return_size,
+            });
+        }
+
+        let mut data = self.memory.read_range(offset as u32, len as u32);
+        data.resize(len as usize, 0);
+        Ok(data)
+    }
+
+    /// Charge hashing gas and compute the BLAKE3 digest of a memory range.
+    fn hash_memory(&mut self, off_reg: usize, len_reg: usize) -> Result<Hash, VmError> {
+        let offset = self.registers.get(off_reg);
+        let len = self.registers.get(len_reg);
+        if len > self.memory.max_size() as u64 {
+            return Err(VmError::MemoryOverflow);
+        }
+        let words = len.div_ceil(32);
+        self.gas.consume(words * self.schedule.hash_per_word)?;
+
+        let mut data = self.memory.read_range(offset as u32, len as u32);
+        data.resize(len as usize, 0);
+        Ok(hash(&data))
+    }
+
+    /// Bytes of calldata in `[offset, offset + len)`, truncated at its end.
+    fn calldata_range(&self, offset: u64, len: u64) -> &[u8] {
+        let size = self.calldata.len() as u64;
+        let start = offset.min(size) as usize;
+        let end = offset.saturating_add(len).min(size) as usize;
+        &self.calldata[start..end]
+    }
+
+    /// Execute CALLDATACOPY: copy calldata into memory.
+    ///
+    /// Operands: `dst_off, off, len`. Bytes past the end of calldata are
+    /// written as zeros.
+    fn execute_calldatacopy(&mut self) -> Result<(), VmError> {
+        let (dst_off_reg, off_reg, len_reg) = self.decode_rrr();
+        let len = self.registers.get(len_reg);
+        if len > self.memory.max_size() as u64 {
+            return Err(VmError::MemoryOverflow);
+        }
+        self.gas
+            .consume(len.div_ceil(32) * self.schedule.copy_per_word)?;
+
+        let mut data = self
+            .calldata_range(self.registers.get(off_reg), len)
+            .to_vec();
+        data.resize(len as usize, 0);
+        let dst_off = self.registers.get(dst_off_reg) as u32;
+        self.memory.store_bytes(dst_off, &data)?;
+        self.pc += 3;
+        Ok(())
+    }
+
+    /// Execute SIGVERIFY: check an Ed25519 signature over a memory range.
+    ///
+    /// Operands: `dst, pk_off, msg_off, msg_len, sig_off`. Reads a 32-byte public
+    /// key and a 64-byte signature from memory; `dst` receives 1 if valid, else 0.
+    fn execute_sigverify(&mut self) -> Result<(), VmError> {
+        let [dst, pk_reg, msg_off_reg, msg_len_reg, sig_reg] = self.decode_regs::<5>();
+
+        let msg_len = self.registers.get(msg_len_reg);
+        if msg_len > self.memory.max_size() as u64 {
+            return Err(VmError::MemoryOverflow);
+        }
+        let pk_bytes = self.read_word(self.registers.get(pk_reg) as u32);
+        let mut sig_bytes = [0u8; 64];
+        let sig = self
+            .memory
+            .read_range(self.registers.get(sig_reg) as u32, 64);
+        sig_bytes[..sig.len()].copy_from_slice(&sig);
+        let mut message = self
+            .memory
+            .read_range(self.registers.get(msg_off_reg) as u32, msg_len as u32);
+        message.resize(msg_len as usize, 0);
+
+        let valid = PublicKey::from_bytes(&pk_bytes)
+            .and_then(|key| key.verify(&message, &Signature::from_bytes(sig_bytes)))
+            .is_ok();
+        self.registers.set(dst, valid as u64);
+        self.pc += 4;
+        Ok(())
+    }
+
+    /// Execute LOG0-LOG4: emit an event with `topic_count` topics.
+    ///
+    /// Operands: `offset, len, topic...`. The data range is read from memory,
+    /// with bytes past the end of memory reading as zero.
+    fn execute_log(&mut self, topic_count: usize) -> Result<(), VmError> {
+        let offset = self.registers.get(self.decode_reg_at(0));
+        let len = self.registers.get(self.decode_reg_at(1));
+        if len > self.memory.max_size() as u64 {
+            return Err(VmError::MemoryOverflow);
+        }
+        let cost = (self.schedule.log_topic * topic_count as u64)
+            .saturating_add(len.saturating_mul(self.schedule.log_data_per_byte));
+        self.gas.consume(cost)?;
+
+        let topics = (0..topic_count)
+            .map(|i| self.registers.get(self.decode_reg_at(2 + i)))
+            .collect();
+        let mut data = self.memory.read_range(offset as u32, len as u32);
+        data.resize(len as usize, 0);
+
+        let event = Log::new(self.address, topics, data);
+        self.inspect(|inspector, vm| inspector.event(vm, &event));
+        self.events.push(event);
+        self.pc += 1 + (2 + topic_count).div_ceil(2);
+        Ok(())
+    }
+
+    /// Execute TRANSFER: send native value from this contract to an address in memory.
+    ///
+    /// Fails the frame if there is no host or the balance cannot cover `amount`.
+    /// The host journals the transfer, so a reverting frame undoes it.
+    fn execute_transfer(&mut self) -> Result<(), VmError> {
+        let (addr_reg, amount_reg) = self.decode_rr();
+        let to = self.read_address(self.registers.get(addr_reg) as u32);
+        let amount = self.registers.get(amount_reg);
+
+        let transferred = self
+            .host
+            .as_ref()
+            .is_some_and(|host| host.transfer(&self.address, &to, amount));
+        if !transferred {
+            return Err(VmError::TransferFailed { amount });
+        }
+
+        self.pc += 2;
+        Ok(())
+    }
+
+    /// Execute CREATE: deploy code from memory as a new contract.
+    ///
+    /// Operands: `dst, addr_off, off, len, value`. On success the 20-byte
+    /// address is written to memory at `addr_off` and `dst` receives its
+    /// first 8 bytes, like ADDRESS. On failure `dst` receives 0 and every
+    /// change made by the attempt is rolled back.
+    fn execute_create(&mut self) -> Result<(), VmError> {
+        let [dst, addr_off_reg, off_reg, len_reg, value_reg] = self.decode_regs::<5>();
+        let len = self.registers.get(len_reg);
+        if len > self.memory.max_size() as u64 {
+            return Err(VmError::MemoryOverflow);
+        }
+        self.gas
+            .consume(len.saturating_mul(self.schedule.create_per_byte))?;
+
+        let mut code = self
+            .memory
+            .read_range(self.registers.get(off_reg) as u32, len as u32);
+        code.resize(len as usize, 0);
+        let value = self.registers.get(value_reg);
+
+        let created = match &self.host {
+            Some(host) if verify(&code).is_ok() => {
+                let checkpoint = host.checkpoint();
+                let created = host.create(&self.address, &code, value);
+                if created.is_none() {
+                    host.revert_to(checkpoint);
+                }
+                created
+            }
+            _ => None,
+        };
+
+        let result = match created {
+            Some(address) => {
+                self.memory
+                    .store_bytes(self.registers.get(addr_off_reg) as u32, address.as_bytes())?;
+                let mut prefix = [0u8; 8];
+                prefix.copy_from_slice(&address.as_bytes()[..8]);
+                u64::from_le_bytes(prefix)
+            }
+            None => 0,
+        };
+        self.registers.set(dst, result);
+        self.pc += 4;
+        Ok(())
+    }
+
+    /// Execute SYSCALL: run an embedder-defined host function.
+    ///
+    /// Operands: `dst, id, off, len, out`. The function receives `len` bytes
+    /// of memory from `off`; its data is written to memory at `out` and its
+    /// value to `dst`. Charges the base cost plus the function's own gas.
+    fn execute_syscall(&mut self) -> Result<(), VmError> {
+        let [dst, id_reg, off_reg, len_reg, out_reg] = self.decode_regs::<5>();
+        let id = self.registers.get(id_reg);
+        let len = self.registers.get(len_reg);
+        if len > self.memory.max_size() as u64 {
+            return Err(VmError::MemoryOverflow);
+        }
+        let mut input = self
+            .memory
+            .read_range(self.registers.get(off_reg) as u32, len as u32);
+        input.resize(len as usize, 0);
+
+        let functions = self
+            .host_functions
+            .clone()
+            .ok_or(VmError::UnknownSyscall(id))?;
+        let cost = functions
+            .gas(id, &input)
+            .ok_or(VmError::UnknownSyscall(id))?;
+        self.gas.consume(cost)?;
+
+        let output = functions
+            .call(self, id, &input)
+            .map_err(|reason| VmError::SyscallFailed { id, reason })?;
+        self.memory
+            .store_bytes(self.registers.get(out_reg) as u32, &output.data)?;
+        self.registers.set(dst, output.value);
+        self.pc += 4;
+        Ok(())
+    }
+
+    /// Execute CALL: run another contract in a child frame.
+    ///
+    /// Operands: `dst, addr_off, value, gas, in_off, in_len, out_off, out_len`.
+    /// The callee address is read as 20 bytes from memory at `addr_off`.
+    /// Input bytes past the end of memory read as zero, and the output range
+    /// is written in full, zero-padding short return data.
+    /// `dst` receives 1 on success and 0 on failure.
+    fn execute_call(&mut self) -> Result<(), VmError> {
+        let [dst, addr_reg, value_reg, gas_reg, in_off_reg, in_len_reg, out_off_reg, out_len_reg] =
+            self.decode_regs::<8>();
+
+        let target = self.read_address(self.registers.get(addr_reg) as u32);
+        let value = self.registers.get(value_reg);
+        if self.is_static && value > 0 {
+            return Err(VmError::StaticViolation(Opcode::CALL));
+        }
+        let in_len = self.registers.get(in_len_reg);
+        let out_len = self.registers.get(out_len_reg);
+        let max_size = self.memory.max_size() as u64;
+        if in_len > max_size || out_len > max_size {
+            return Err(VmError::MemoryOverflow);
+        }
+        let mut input = self
+            .memory
+            .read_range(self.registers.get(in_off_reg) as u32, in_len as u32);
+        input.resize(in_len as usize, 0);
+
+        // Forward at most 63/64 of the remaining gas to the callee.
+        let remaining = self.gas.remaining();
+        let child_gas = self.registers.get(gas_reg).min(remaining - remaining / 64);
+        self.gas.consume(child_gas)?;
+
+        self.inspect(|inspector, vm| inspector.call(vm, &target, value, &input, child_gas));
+        let outcome = self.call_frame(target, value, child_gas, &input);
+        self.inspect(|inspector, vm| {
+            inspector.call_end(
+                vm,
+                outcome.success,
+                child_gas - outcome.gas_left,
+                &outcome.return_data,
+            )
+        });
+        self.gas.refund(outcome.gas_left);
+
+        // The output range is filled in full, zero-padding short return data,
+        // so its memory growth is charged like any other write.
+        let mut output = outcome.return_data;
+        output.resize(out_len as usize, 0);
+        let out_off = self.registers.get(out_off_reg) as u32;
+        self.memory.store_bytes(out_off, &output)?;
+
+        self.registers.set(dst, outcome.success as u64);
+        self.pc += 5;
+        Ok(())
+    }
+
+    /// Run `target`'s code in a child VM, rolling back its changes on failure.
+    fn call_frame(&mut self, target: Address, value: u64, gas: u64, input: &[u8]) -> CallOutcome {
+        let Some(host) = self.host.clone() else {
+            return CallOutcome::failure(gas);
+        };
+        if self.depth >= MAX_CALL_DEPTH {
+            return CallOutcome::failure(gas);
+        }
+
+        let checkpoint = host.checkpoint();
+        if value > 0 && !host.transfer(&self.address, &target, value) {
+            host.revert_to(checkpoint);
+            return CallOutcome::failure(gas);
+        }
+
+        if let Some(outcome) = host.precompile(&target, input, gas) {
+            if !outcome.success {
+                host.revert_to(checkpoint);
+            }
+            return CallOutcome {
+                success: outcome.success,
+                gas_left: gas.saturating_sub(outcome.gas_used),
+                return_data: outcome.output,
+            };
+        }
+
+        // Calls to accounts without code only move value.
+        let Some(program) = host.program(&target) else {
+            return CallOutcome {
+                success: true,
+                gas_left: gas,
+                return_data: Vec::new(),
+            };
+        };
+
+        let mut child = Vm::from_program(
+            program,
+            gas,
+            self.address,
+            target,
+            value,
+            self.schedule.clone(),
+        );
+        child.set_block_context(self.block.clone());
+        child.set_tx_context(self.tx.clone());
+        child.depth = self.depth + 1;
+        child.is_static = self.is_static;
+        child.max_return_depth = self.max_return_depth;
+        child.set_storage(host.storage(&target));
+        child.set_host(host.clone());
+        child.host_functions = self.host_functions.clone();
+        child.set_max_return_size(self.max_return_size);
+        child.set_calldata(input.to_vec());
+        if let Some(inspector) = &self.inspector {
+            child.set_inspector(inspector.clone());
+        }
+
+        match child.run() {
+            Ok(result) if result.success => {
+                self.logs.extend(result.logs);
+                self.events.extend(result.events);
+                CallOutcome {
+                    success: true,
+                    gas_left: child.gas_remaining(),
+                    return_data: result.return_data,
+                }
+            }
+            _ => {
+                host.revert_to(checkpoint);
+                CallOutcome::failure(child.gas_remaining())
+            }
+        }
+    }
+
+    /// Read a 20-byte address from memory.
+    fn read_address(&self, offset: u32) -> Address {
+        let bytes = self.memory.read_range(offset, 20);
+        let mut address = [0u8; 20];
+        address[..bytes.len()].copy_from_slice(&bytes);
+        Address(address)
+    }
 }
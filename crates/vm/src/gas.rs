@@ -1,6 +1,9 @@
 //! Gas metering.
 
 use crate::executor::VmError;
+use crate::opcodes::Opcode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Gas costs for operations.
 pub struct GasCosts;
@@ -20,15 +23,205 @@ impl GasCosts {
     pub const MEMORY_READ: u64 = 3;
     pub const MEMORY_WRITE: u64 = 3;
     pub const MEMORY_GROW_PER_BYTE: u64 = 1;
+    pub const COPY_PER_WORD: u64 = 3;
 
     // Tier 5: Storage (expensive!)
     pub const SLOAD: u64 = 100; // Read from storage
     pub const SSTORE_SET: u64 = 20000; // Write to empty slot
     pub const SSTORE_RESET: u64 = 5000; // Overwrite existing slot
+    pub const TLOAD: u64 = 100; // Transient storage, discarded after the transaction
+    pub const TSTORE: u64 = 100;
+
+    // Hashing
+    pub const HASH: u64 = 30;
+    pub const HASH_PER_WORD: u64 = 6;
+    pub const SIGVERIFY: u64 = 3000;
 
     // Control flow
     pub const JUMP: u64 = 8;
     pub const CALL: u64 = 700;
+
+    // Accounts
+    pub const BALANCE: u64 = 100;
+    pub const BLOCKHASH: u64 = 20;
+    pub const TRANSFER: u64 = 9000;
+    pub const CREATE: u64 = 32000;
+    pub const CREATE_PER_BYTE: u64 = 200; // Deployed code
+
+    // Events
+    pub const LOG: u64 = 375;
+    pub const LOG_TOPIC: u64 = 375;
+    pub const LOG_DATA_PER_BYTE: u64 = 8;
+}
+
+/// Gas prices used by a VM instance.
+///
+/// Each opcode has a fixed price, returned by [`GasSchedule::cost`]. It is
+/// the opcode's entry in `opcodes` if there is one, and otherwise the price of
+/// the tier the opcode belongs to. The remaining fields price the parts that
+/// scale with the operands, such as hashed words or storage writes.
+///
+/// The defaults match [`GasCosts`]. Fields missing from a serialized schedule
+/// fall back to the default, so configs only need to list the prices they
+/// change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GasSchedule {
+    pub base: u64,
+    pub low: u64,
+    pub mid: u64,
+    pub memory_read: u64,
+    pub memory_write: u64,
+    pub memory_grow_per_byte: u64,
+    pub copy_per_word: u64,
+    pub sload: u64,
+    pub sstore_set: u64,
+    pub sstore_reset: u64,
+    pub tload: u64,
+    pub tstore: u64,
+    pub hash: u64,
+    pub hash_per_word: u64,
+    pub sigverify: u64,
+    pub jump: u64,
+    pub call: u64,
+    pub balance: u64,
+    pub blockhash: u64,
+    pub transfer: u64,
+    pub create: u64,
+    pub create_per_byte: u64,
+    pub log: u64,
+    pub log_topic: u64,
+    pub log_data_per_byte: u64,
+    /// Per-opcode prices overriding the tier price, keyed by mnemonic.
+    pub opcodes: BTreeMap<Opcode, u64>,
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Self {
+            base: GasCosts::BASE,
+            low: GasCosts::LOW,
+            mid: GasCosts::MID,
+            memory_read: GasCosts::MEMORY_READ,
+            memory_write: GasCosts::MEMORY_WRITE,
+            memory_grow_per_byte: GasCosts::MEMORY_GROW_PER_BYTE,
+            copy_per_word: GasCosts::COPY_PER_WORD,
+            sload: GasCosts::SLOAD,
+            sstore_set: GasCosts::SSTORE_SET,
+            sstore_reset: GasCosts::SSTORE_RESET,
+            tload: GasCosts::TLOAD,
+            tstore: GasCosts::TSTORE,
+            hash: GasCosts::HASH,
+            hash_per_word: GasCosts::HASH_PER_WORD,
+            sigverify: GasCosts::SIGVERIFY,
+            jump: GasCosts::JUMP,
+            call: GasCosts::CALL,
+            balance: GasCosts::BALANCE,
+            blockhash: GasCosts::BLOCKHASH,
+            transfer: GasCosts::TRANSFER,
+            create: GasCosts::CREATE,
+            create_per_byte: GasCosts::CREATE_PER_BYTE,
+            log: GasCosts::LOG,
+            log_topic: GasCosts::LOG_TOPIC,
+            log_data_per_byte: GasCosts::LOG_DATA_PER_BYTE,
+            opcodes: BTreeMap::new(),
+        }
+    }
+}
+
+impl GasSchedule {
+    /// Price `opcode` at `cost`, replacing its tier price.
+    pub fn with_opcode(mut self, opcode: Opcode, cost: u64) -> Self {
+        self.opcodes.insert(opcode, cost);
+        self
+    }
+
+    /// Fixed price charged before executing `opcode`.
+    pub fn cost(&self, opcode: Opcode) -> u64 {
+        match self.opcodes.get(&opcode) {
+            Some(&cost) => cost,
+            None => self.tier_cost(opcode),
+        }
+    }
+
+    /// Price of the tier `opcode` belongs to.
+    fn tier_cost(&self, opcode: Opcode) -> u64 {
+        use Opcode::*;
+        match opcode {
+            HALT | NOP | RET | REVERT => GasCosts::ZERO,
+            // Storage writes are priced entirely by `sstore_set`/`sstore_reset`.
+            SSTORE | SSTOREM => GasCosts::ZERO,
+
+            ADD | SUB | ADDC | SUBC | ADDI | AND | OR | XOR | NOT | EQ | NE | LT | GT | LE | GE
+            | ISZERO | SLT | SGT | LOADI | MOV | LOG | MSIZE | CALLDATASIZE | CALLER
+            | CALLVALUE | ADDRESS | BLOCKNUMBER | TIMESTAMP | CHAINID | GASPRICE | GAS
+            | SYSCALL => self.base,
+            MUL | MULC | MULHI | ADDREQ => self.low,
+            DIV | MOD | SDIV | SMOD | SHL | SHR | SAR | SELFBALANCE => self.mid,
+
+            LOAD8 | LOAD64 | MCOPY | CALLDATALOAD | RETURN | REVERTD => self.memory_read,
+            STORE8 | STORE64 | CALLDATACOPY | CALLERM | ADDRESSM | ORIGIN | COINBASE => {
+                self.memory_write
+            }
+
+            JUMP | JUMPI | JSUB | JSUBI | JRET => self.jump,
+            CALL => self.call,
+            SLOAD | SLOADM => self.sload,
+            TLOAD => self.tload,
+            TSTORE => self.tstore,
+            HASH | HASH64 => self.hash,
+            SIGVERIFY => self.sigverify,
+            BALANCE => self.balance,
+            BLOCKHASH => self.blockhash,
+            TRANSFER => self.transfer,
+            CREATE => self.create,
+            LOG0 | LOG1 | LOG2 | LOG3 | LOG4 => self.log,
+        }
+    }
+
+    /// Calculate gas for growing memory from `current_size` to `new_size` bytes.
+    pub fn memory_expansion_cost(&self, current_size: usize, new_size: usize) -> u64 {
+        if new_size <= current_size {
+            return 0;
+        }
+        let expansion = (new_size - current_size) as u64;
+        expansion.saturating_mul(self.memory_grow_per_byte)
+    }
+}
+
+/// Gas schedules keyed by the block height at which they take effect.
+///
+/// The schedule for a block is the one with the highest activation height
+/// not above the block's height.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasSchedules(BTreeMap<u64, GasSchedule>);
+
+impl GasSchedules {
+    /// Use a single schedule from genesis onwards.
+    pub fn new(genesis: GasSchedule) -> Self {
+        Self(BTreeMap::from([(0, genesis)]))
+    }
+
+    /// Schedule a new set of prices starting at `height`.
+    pub fn with_activation(mut self, height: u64, schedule: GasSchedule) -> Self {
+        self.0.insert(height, schedule);
+        self
+    }
+
+    /// The schedule in effect at `height`.
+    pub fn at(&self, height: u64) -> GasSchedule {
+        self.0
+            .range(..=height)
+            .next_back()
+            .map(|(_, schedule)| schedule.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for GasSchedules {
+    fn default() -> Self {
+        Self::new(GasSchedule::default())
+    }
 }
 
 /// Gas meter tracks remaining gas.
@@ -58,6 +251,13 @@ impl GasMeter {
         Ok(())
     }
 
+    /// Return unused gas, e.g. what a child call frame did not spend.
+    pub fn refund(&mut self, amount: u64) {
+        let amount = amount.min(self.used);
+        self.remaining += amount;
+        self.used -= amount;
+    }
+
     pub fn remaining(&self) -> u64 {
         self.remaining
     }
@@ -65,13 +265,59 @@ impl GasMeter {
     pub fn used(&self) -> u64 {
         self.used
     }
+}
 
-    /// Calculate gas for memory expansion.
-    pub fn memory_expansion_cost(current_size: usize, new_size: usize) -> u64 {
-        if new_size <= current_size {
-            return 0;
-        }
-        let expansion = (new_size - current_size) as u64;
-        expansion * GasCosts::MEMORY_GROW_PER_BYTE
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_selected_by_height() {
+        let expensive = GasSchedule {
+            sload: 800,
+            ..GasSchedule::default()
+        };
+        let schedules = GasSchedules::default().with_activation(100, expensive.clone());
+
+        assert_eq!(schedules.at(0), GasSchedule::default());
+        assert_eq!(schedules.at(99), GasSchedule::default());
+        assert_eq!(schedules.at(100), expensive);
+        assert_eq!(schedules.at(5000), expensive);
+    }
+
+    #[test]
+    fn test_partial_schedule_uses_defaults() {
+        let schedule: GasSchedule = serde_json::from_str(r#"{"sload": 250}"#).unwrap();
+
+        assert_eq!(schedule.sload, 250);
+        assert_eq!(schedule.sstore_set, GasCosts::SSTORE_SET);
+    }
+
+    #[test]
+    fn test_opcode_override_at_activation() {
+        let schedules = GasSchedules::default()
+            .with_activation(100, GasSchedule::default().with_opcode(Opcode::SLOADM, 800));
+
+        assert_eq!(schedules.at(99).cost(Opcode::SLOADM), GasCosts::SLOAD);
+        assert_eq!(schedules.at(100).cost(Opcode::SLOADM), 800);
+        assert_eq!(schedules.at(100).cost(Opcode::SLOAD), GasCosts::SLOAD);
+    }
+
+    #[test]
+    fn test_opcode_override_from_config() {
+        let schedule: GasSchedule =
+            serde_json::from_str(r#"{"low": 4, "opcodes": {"HASH64": 12}}"#).unwrap();
+
+        assert_eq!(schedule.cost(Opcode::HASH64), 12);
+        assert_eq!(schedule.cost(Opcode::HASH), GasCosts::HASH);
+        assert_eq!(schedule.cost(Opcode::MUL), 4);
+    }
+
+    #[test]
+    fn test_memory_expansion_cost() {
+        let schedule = GasSchedule::default();
+
+        assert_eq!(schedule.memory_expansion_cost(64, 32), 0);
+        assert_eq!(schedule.memory_expansion_cost(32, 64), 32);
     }
 }
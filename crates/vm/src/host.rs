@@ -0,0 +1,64 @@
+//! World-state access for nested execution frames.
+
+use crate::executor::StorageBackend;
+use crate::program::Program;
+use minichain_core::Address;
+use std::sync::Arc;
+
+/// Result of running a precompiled contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrecompileOutcome {
+    pub success: bool,
+    /// Gas charged to the caller; all of the forwarded gas on failure.
+    pub gas_used: u64,
+    pub output: Vec<u8>,
+}
+
+/// Interface the embedder provides so contracts can interact with other accounts.
+///
+/// All methods take `&self`: a host is shared between the frames of one
+/// transaction, so implementations keep their pending state behind interior
+/// mutability and record changes in a journal that can be rolled back.
+pub trait Host {
+    /// Load the runtime bytecode deployed at `address`, if any.
+    fn code(&self, address: &Address) -> Option<Vec<u8>>;
+
+    /// Decoded program deployed at `address`, if any.
+    ///
+    /// Decodes [`Host::code`] on every call by default; hosts that keep a
+    /// [`ProgramCache`](crate::program::ProgramCache) should override it.
+    fn program(&self, address: &Address) -> Option<Arc<Program>> {
+        self.code(address).map(|code| Arc::new(Program::new(code)))
+    }
+
+    /// Run the precompiled contract at `address` with `gas` available.
+    ///
+    /// Returns `None` if `address` is not a precompile, in which case the
+    /// call runs the code deployed there.
+    #[allow(unused_variables)]
+    fn precompile(&self, address: &Address, input: &[u8], gas: u64) -> Option<PrecompileOutcome> {
+        None
+    }
+
+    /// Storage view for the contract at `address`.
+    fn storage(&self, address: &Address) -> Box<dyn StorageBackend + '_>;
+
+    /// Current native balance of `address`, including pending changes.
+    fn balance(&self, address: &Address) -> u64;
+
+    /// Move native value between accounts. Returns false if `from` cannot cover it.
+    fn transfer(&self, from: &Address, to: &Address, amount: u64) -> bool;
+
+    /// Deploy `code` as a new contract funded with `value` from `creator`.
+    ///
+    /// The address is derived from `creator` and its nonce, which is then
+    /// incremented. Returns `None`, leaving the caller to roll back, if the
+    /// address is taken or `creator` cannot cover `value`.
+    fn create(&self, creator: &Address, code: &[u8], value: u64) -> Option<Address>;
+
+    /// Mark the current point in the change journal.
+    fn checkpoint(&self) -> usize;
+
+    /// Undo every change recorded after `checkpoint`.
+    fn revert_to(&self, checkpoint: usize);
+}
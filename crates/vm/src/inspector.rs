@@ -0,0 +1,254 @@
+//! Execution inspection hooks.
+//!
+//! An [`Inspector`] attached with [`Vm::set_inspector`] is called around
+//! every instruction and on storage access, logs, nested calls and reverts.
+//! The same inspector is handed down to child frames, so it sees the whole
+//! call tree of a transaction.
+
+use crate::executor::{Vm, VmError};
+use crate::opcodes::Opcode;
+use minichain_core::{Address, Log};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Callbacks invoked by the VM during execution. Every method defaults to a no-op.
+///
+/// `vm` is the frame the event happened in.
+#[allow(unused_variables)]
+pub trait Inspector {
+    /// Before the instruction at `vm.pc()` executes.
+    fn step(&mut self, vm: &Vm<'_>) {}
+
+    /// After an instruction executed successfully.
+    fn step_end(&mut self, vm: &Vm<'_>) {}
+
+    /// After an instruction failed, ending the frame. Called for REVERT too,
+    /// after `revert`.
+    fn step_error(&mut self, vm: &Vm<'_>, error: &VmError) {}
+
+    /// A storage slot was read.
+    fn sload(&mut self, vm: &Vm<'_>, key: &[u8; 32], value: &[u8; 32]) {}
+
+    /// A storage slot was written; `old` is the value it replaced.
+    fn sstore(&mut self, vm: &Vm<'_>, key: &[u8; 32], old: &[u8; 32], new: &[u8; 32]) {}
+
+    /// The debug LOG instruction emitted a value.
+    fn log(&mut self, vm: &Vm<'_>, value: u64) {}
+
+    /// LOG0-LOG4 emitted an event.
+    fn event(&mut self, vm: &Vm<'_>, event: &Log) {}
+
+    /// A nested call is about to run `target`'s code.
+    fn call(&mut self, vm: &Vm<'_>, target: &Address, value: u64, input: &[u8], gas: u64) {}
+
+    /// The nested call started by the last unmatched `call` returned.
+    fn call_end(&mut self, vm: &Vm<'_>, success: bool, gas_used: u64, output: &[u8]) {}
+
+    /// The frame executed REVERT. Its changes are rolled back.
+    fn revert(&mut self, vm: &Vm<'_>, data: &[u8]) {}
+}
+
+/// A single trace entry.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceStep {
+    pub pc: usize,
+    pub op: String,
+    pub gas: u64,
+    pub gas_cost: u64,
+    pub depth: usize,
+    pub memory_size: usize,
+    pub registers: [u64; 16],
+}
+
+/// Records every executed instruction for JSON output.
+///
+/// Register values and gas are captured before the instruction runs;
+/// `gas_cost` includes the gas used by any nested call it made.
+#[derive(Debug, Default)]
+pub struct JsonTracer {
+    steps: Vec<TraceStep>,
+    open: Vec<usize>,
+}
+
+impl JsonTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    /// The trace as a JSON array of steps.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(&self.steps).expect("trace steps always serialize")
+    }
+}
+
+impl Inspector for JsonTracer {
+    fn step(&mut self, vm: &Vm<'_>) {
+        self.open.push(self.steps.len());
+        self.steps.push(TraceStep {
+            pc: vm.pc(),
+            op: vm
+                .current_opcode()
+                .map_or_else(|| "INVALID".to_string(), |op| format!("{op:?}")),
+            gas: vm.gas_remaining(),
+            gas_cost: 0,
+            depth: vm.depth(),
+            memory_size: vm.memory().size(),
+            registers: *vm.get_registers(),
+        });
+    }
+
+    fn step_end(&mut self, vm: &Vm<'_>) {
+        // Steps of failed child frames never see `step_end`; skip past them.
+        while let Some(index) = self.open.pop() {
+            let step = &mut self.steps[index];
+            if step.depth == vm.depth() {
+                step.gas_cost = step.gas.saturating_sub(vm.gas_remaining());
+                break;
+            }
+        }
+    }
+}
+
+/// A storage slot of a specific contract.
+pub type SlotKey = (Address, [u8; 32]);
+
+/// Original and final value of a storage slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotDiff {
+    pub original: [u8; 32],
+    pub current: [u8; 32],
+}
+
+/// Collects storage changes per contract, dropping those of failed nested calls.
+///
+/// Changes of a failed top-level frame are kept; the embedder discards the
+/// whole transaction in that case.
+#[derive(Debug, Default)]
+pub struct StorageDiffInspector {
+    slots: HashMap<SlotKey, SlotDiff>,
+    journal: Vec<(SlotKey, Option<SlotDiff>)>,
+    checkpoints: Vec<usize>,
+}
+
+impl StorageDiffInspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Slots whose final value differs from the original, keyed by contract and slot.
+    pub fn diff(&self) -> HashMap<SlotKey, SlotDiff> {
+        self.slots
+            .iter()
+            .filter(|(_, slot)| slot.original != slot.current)
+            .map(|(key, slot)| (*key, *slot))
+            .collect()
+    }
+}
+
+impl Inspector for StorageDiffInspector {
+    fn sstore(&mut self, vm: &Vm<'_>, key: &[u8; 32], old: &[u8; 32], new: &[u8; 32]) {
+        let slot_key = (*vm.address(), *key);
+        let previous = self.slots.get(&slot_key).copied();
+        self.journal.push((slot_key, previous));
+        self.slots.insert(
+            slot_key,
+            SlotDiff {
+                original: previous.map_or(*old, |slot| slot.original),
+                current: *new,
+            },
+        );
+    }
+
+    fn call(&mut self, _vm: &Vm<'_>, _target: &Address, _value: u64, _input: &[u8], _gas: u64) {
+        self.checkpoints.push(self.journal.len());
+    }
+
+    fn call_end(&mut self, _vm: &Vm<'_>, success: bool, _gas_used: u64, _output: &[u8]) {
+        let Some(checkpoint) = self.checkpoints.pop() else {
+            return;
+        };
+        if success {
+            return;
+        }
+        for (slot_key, previous) in self.journal.drain(checkpoint..).rev() {
+            match previous {
+                Some(slot) => self.slots.insert(slot_key, slot),
+                None => self.slots.remove(&slot_key),
+            };
+        }
+    }
+}
+
+/// Counts executed instructions per opcode across all frames.
+#[derive(Debug, Default)]
+pub struct OpcodeCounter {
+    counts: HashMap<Opcode, u64>,
+}
+
+impl OpcodeCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(&self, opcode: Opcode) -> u64 {
+        self.counts.get(&opcode).copied().unwrap_or(0)
+    }
+
+    pub fn counts(&self) -> &HashMap<Opcode, u64> {
+        &self.counts
+    }
+
+    /// Total number of executed instructions.
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+}
+
+impl Inspector for OpcodeCounter {
+    fn step(&mut self, vm: &Vm<'_>) {
+        if let Some(opcode) = vm.current_opcode() {
+            *self.counts.entry(opcode).or_default() += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::GasSchedule;
+
+    #[test]
+    fn test_storage_diff_drops_failed_call() {
+        let vm = Vm::new(
+            vec![0x00],
+            1000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            GasSchedule::default(),
+        );
+        let zero = [0u8; 32];
+        let (one, two) = ([1u8; 32], [2u8; 32]);
+        let mut inspector = StorageDiffInspector::new();
+
+        inspector.sstore(&vm, &one, &zero, &one);
+        inspector.call(&vm, &Address::ZERO, 0, &[], 100);
+        inspector.sstore(&vm, &one, &one, &two);
+        inspector.sstore(&vm, &two, &zero, &two);
+        inspector.call_end(&vm, false, 100, &[]);
+
+        let diff = inspector.diff();
+        assert_eq!(diff.len(), 1);
+        assert_eq!(
+            diff[&(Address::ZERO, one)],
+            SlotDiff {
+                original: zero,
+                current: one
+            }
+        );
+    }
+}
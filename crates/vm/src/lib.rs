@@ -1,13 +1,32 @@
 //! Register-based virtual machine for minichain.
 
+pub mod context;
+pub mod debugger;
 pub mod executor;
 pub mod gas;
+pub mod host;
+pub mod inspector;
 pub mod memory;
 pub mod opcodes;
-pub mod tracer;
+pub mod profiler;
+pub mod program;
+pub mod syscall;
+pub mod verify;
 
-pub use executor::{ExecutionResult, StorageBackend, Vm, VmError};
-pub use gas::{GasCosts, GasMeter};
+pub use context::{BlockContext, TxContext, BLOCKHASH_WINDOW};
+pub use debugger::{Debugger, StopReason, Watchpoint};
+pub use executor::{
+    ExecutionResult, ExecutionStatus, StorageBackend, Vm, VmError, DEFAULT_MAX_RETURN_DEPTH,
+    DEFAULT_MAX_RETURN_SIZE, MAX_CALL_DEPTH,
+};
+pub use gas::{GasCosts, GasMeter, GasSchedule, GasSchedules};
+pub use host::{Host, PrecompileOutcome};
+pub use inspector::{
+    Inspector, JsonTracer, OpcodeCounter, SlotDiff, StorageDiffInspector, TraceStep,
+};
 pub use memory::{Memory, Registers, NUM_REGISTERS};
 pub use opcodes::Opcode;
-pub use tracer::{TraceStep, Tracer};
+pub use profiler::{GasProfiler, GasStat};
+pub use program::{Instruction, Program, ProgramCache};
+pub use syscall::{HostFunctions, SyscallOutput};
+pub use verify::{verify, VerifyError};
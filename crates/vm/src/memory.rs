@@ -130,4 +130,8 @@ impl Memory {
     pub fn size(&self) -> usize {
         self.data.len()
     }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
 }
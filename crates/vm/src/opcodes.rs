@@ -1,7 +1,9 @@
 //! Opcode definitions for the VM.
 
+use serde::{Deserialize, Serialize};
+
 /// All VM opcodes.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Opcode {
     // Control Flow (0x00-0x0F)
@@ -11,6 +13,11 @@ pub enum Opcode {
     JUMPI = 0x03,
     CALL = 0x04,
     RET = 0x05,
+    RETURN = 0x06,
+    JSUB = 0x07,
+    JRET = 0x08,
+    JSUBI = 0x09,
+    REVERTD = 0x0E,
     REVERT = 0x0F,
 
     // Arithmetic (0x10-0x1F)
@@ -20,6 +27,12 @@ pub enum Opcode {
     DIV = 0x13,
     MOD = 0x14,
     ADDI = 0x15,
+    SDIV = 0x16,
+    SMOD = 0x17,
+    ADDC = 0x18,
+    SUBC = 0x19,
+    MULC = 0x1A,
+    MULHI = 0x1B,
 
     // Bitwise (0x20-0x2F)
     AND = 0x20,
@@ -28,6 +41,7 @@ pub enum Opcode {
     NOT = 0x23,
     SHL = 0x24,
     SHR = 0x25,
+    SAR = 0x26,
 
     // Comparison (0x30-0x3F)
     EQ = 0x30,
@@ -37,6 +51,8 @@ pub enum Opcode {
     LE = 0x34,
     GE = 0x35,
     ISZERO = 0x36,
+    SLT = 0x37,
+    SGT = 0x38,
 
     // Memory - RAM (0x40-0x4F)
     LOAD8 = 0x40,
@@ -45,10 +61,24 @@ pub enum Opcode {
     STORE64 = 0x43,
     MSIZE = 0x44,
     MCOPY = 0x45,
+    CALLDATASIZE = 0x46,
+    CALLDATALOAD = 0x47,
+    CALLDATACOPY = 0x48,
 
     // Storage - Disk (0x50-0x5F)
     SLOAD = 0x50,
     SSTORE = 0x51,
+    SLOADM = 0x52,
+    SSTOREM = 0x53,
+
+    // Storage - Transient (cleared after each transaction)
+    TLOAD = 0x54,
+    TSTORE = 0x55,
+
+    // Crypto (0x60-0x6F)
+    HASH = 0x60,
+    HASH64 = 0x61,
+    SIGVERIFY = 0x62,
 
     // Immediate (0x70-0x7F)
     LOADI = 0x70,
@@ -61,6 +91,28 @@ pub enum Opcode {
     BLOCKNUMBER = 0x83,
     TIMESTAMP = 0x84,
     GAS = 0x85,
+    CALLERM = 0x86,
+    ADDRESSM = 0x87,
+    ADDREQ = 0x88,
+    BALANCE = 0x89,
+    SELFBALANCE = 0x8A,
+    CHAINID = 0x8B,
+    ORIGIN = 0x8C,
+    GASPRICE = 0x8D,
+    COINBASE = 0x8E,
+    BLOCKHASH = 0x8F,
+
+    // System (0x90-0x9F)
+    TRANSFER = 0x90,
+    CREATE = 0x91,
+    SYSCALL = 0x92,
+
+    // Events (0xA0-0xAF)
+    LOG0 = 0xA0,
+    LOG1 = 0xA1,
+    LOG2 = 0xA2,
+    LOG3 = 0xA3,
+    LOG4 = 0xA4,
 
     // Debug (0xF0-0xFF)
     LOG = 0xF0,
@@ -76,6 +128,11 @@ impl Opcode {
             0x03 => Some(Opcode::JUMPI),
             0x04 => Some(Opcode::CALL),
             0x05 => Some(Opcode::RET),
+            0x06 => Some(Opcode::RETURN),
+            0x07 => Some(Opcode::JSUB),
+            0x08 => Some(Opcode::JRET),
+            0x09 => Some(Opcode::JSUBI),
+            0x0E => Some(Opcode::REVERTD),
             0x0F => Some(Opcode::REVERT),
 
             0x10 => Some(Opcode::ADD),
@@ -84,6 +141,12 @@ impl Opcode {
             0x13 => Some(Opcode::DIV),
             0x14 => Some(Opcode::MOD),
             0x15 => Some(Opcode::ADDI),
+            0x16 => Some(Opcode::SDIV),
+            0x17 => Some(Opcode::SMOD),
+            0x18 => Some(Opcode::ADDC),
+            0x19 => Some(Opcode::SUBC),
+            0x1A => Some(Opcode::MULC),
+            0x1B => Some(Opcode::MULHI),
 
             0x20 => Some(Opcode::AND),
             0x21 => Some(Opcode::OR),
@@ -91,6 +154,7 @@ impl Opcode {
             0x23 => Some(Opcode::NOT),
             0x24 => Some(Opcode::SHL),
             0x25 => Some(Opcode::SHR),
+            0x26 => Some(Opcode::SAR),
 
             0x30 => Some(Opcode::EQ),
             0x31 => Some(Opcode::NE),
@@ -99,6 +163,8 @@ impl Opcode {
             0x34 => Some(Opcode::LE),
             0x35 => Some(Opcode::GE),
             0x36 => Some(Opcode::ISZERO),
+            0x37 => Some(Opcode::SLT),
+            0x38 => Some(Opcode::SGT),
 
             0x40 => Some(Opcode::LOAD8),
             0x41 => Some(Opcode::LOAD64),
@@ -106,9 +172,20 @@ impl Opcode {
             0x43 => Some(Opcode::STORE64),
             0x44 => Some(Opcode::MSIZE),
             0x45 => Some(Opcode::MCOPY),
+            0x46 => Some(Opcode::CALLDATASIZE),
+            0x47 => Some(Opcode::CALLDATALOAD),
+            0x48 => Some(Opcode::CALLDATACOPY),
 
             0x50 => Some(Opcode::SLOAD),
             0x51 => Some(Opcode::SSTORE),
+            0x52 => Some(Opcode::SLOADM),
+            0x53 => Some(Opcode::SSTOREM),
+            0x54 => Some(Opcode::TLOAD),
+            0x55 => Some(Opcode::TSTORE),
+
+            0x60 => Some(Opcode::HASH),
+            0x61 => Some(Opcode::HASH64),
+            0x62 => Some(Opcode::SIGVERIFY),
 
             0x70 => Some(Opcode::LOADI),
             0x71 => Some(Opcode::MOV),
@@ -119,6 +196,26 @@ impl Opcode {
             0x83 => Some(Opcode::BLOCKNUMBER),
             0x84 => Some(Opcode::TIMESTAMP),
             0x85 => Some(Opcode::GAS),
+            0x86 => Some(Opcode::CALLERM),
+            0x87 => Some(Opcode::ADDRESSM),
+            0x88 => Some(Opcode::ADDREQ),
+            0x89 => Some(Opcode::BALANCE),
+            0x8A => Some(Opcode::SELFBALANCE),
+            0x8B => Some(Opcode::CHAINID),
+            0x8C => Some(Opcode::ORIGIN),
+            0x8D => Some(Opcode::GASPRICE),
+            0x8E => Some(Opcode::COINBASE),
+            0x8F => Some(Opcode::BLOCKHASH),
+
+            0x90 => Some(Opcode::TRANSFER),
+            0x91 => Some(Opcode::CREATE),
+            0x92 => Some(Opcode::SYSCALL),
+
+            0xA0 => Some(Opcode::LOG0),
+            0xA1 => Some(Opcode::LOG1),
+            0xA2 => Some(Opcode::LOG2),
+            0xA3 => Some(Opcode::LOG3),
+            0xA4 => Some(Opcode::LOG4),
 
             0xF0 => Some(Opcode::LOG),
 
@@ -130,19 +227,28 @@ impl Opcode {
     pub fn instruction_size(&self) -> usize {
         match self {
             // No operands (1 byte total)
-            Opcode::HALT | Opcode::NOP | Opcode::RET => 1,
+            Opcode::HALT | Opcode::NOP | Opcode::RET | Opcode::JRET | Opcode::REVERT => 1,
 
             // One register (2 bytes: opcode + register)
             Opcode::JUMP
+            | Opcode::JSUB
             | Opcode::NOT
             | Opcode::LOG
             | Opcode::MSIZE
+            | Opcode::CALLDATASIZE
             | Opcode::CALLER
             | Opcode::CALLVALUE
             | Opcode::ADDRESS
             | Opcode::BLOCKNUMBER
             | Opcode::TIMESTAMP
-            | Opcode::GAS => 2,
+            | Opcode::GAS
+            | Opcode::CALLERM
+            | Opcode::ADDRESSM
+            | Opcode::SELFBALANCE
+            | Opcode::CHAINID
+            | Opcode::ORIGIN
+            | Opcode::GASPRICE
+            | Opcode::COINBASE => 2,
 
             // Two registers (2 bytes: opcode + packed registers)
             Opcode::MOV
@@ -150,9 +256,21 @@ impl Opcode {
             | Opcode::STORE8
             | Opcode::LOAD64
             | Opcode::STORE64
+            | Opcode::CALLDATALOAD
             | Opcode::SLOAD
             | Opcode::SSTORE
-            | Opcode::ISZERO => 2,
+            | Opcode::SLOADM
+            | Opcode::SSTOREM
+            | Opcode::TLOAD
+            | Opcode::TSTORE
+            | Opcode::BALANCE
+            | Opcode::BLOCKHASH
+            | Opcode::TRANSFER
+            | Opcode::ISZERO
+            | Opcode::RETURN
+            | Opcode::REVERTD
+            | Opcode::JUMPI
+            | Opcode::LOG0 => 2,
 
             // Three registers (3 bytes: opcode + 2 packed register bytes)
             Opcode::ADD
@@ -165,6 +283,15 @@ impl Opcode {
             | Opcode::XOR
             | Opcode::SHL
             | Opcode::SHR
+            | Opcode::SDIV
+            | Opcode::SMOD
+            | Opcode::ADDC
+            | Opcode::SUBC
+            | Opcode::MULC
+            | Opcode::MULHI
+            | Opcode::SAR
+            | Opcode::SLT
+            | Opcode::SGT
             | Opcode::EQ
             | Opcode::NE
             | Opcode::LT
@@ -172,13 +299,46 @@ impl Opcode {
             | Opcode::LE
             | Opcode::GE
             | Opcode::MCOPY
-            | Opcode::JUMPI => 3,
+            | Opcode::CALLDATACOPY
+            | Opcode::HASH
+            | Opcode::HASH64
+            | Opcode::ADDREQ => 3,
+
+            // Data range + topics (offset, len, then up to four topic registers)
+            Opcode::LOG1 | Opcode::LOG2 => 3,
+            Opcode::LOG3 | Opcode::LOG4 => 4,
+
+            // Five registers (4 bytes: opcode + 3 packed register bytes)
+            Opcode::SIGVERIFY | Opcode::CREATE | Opcode::SYSCALL => 4,
 
-            // Register + 64-bit immediate (10 bytes)
-            Opcode::LOADI => 10, // 1 (opcode) + 1 (reg) + 8 (immediate)
+            // Eight registers (5 bytes: opcode + 4 packed register bytes)
+            Opcode::CALL => 5,
 
-            // Fallback
-            _ => 2,
+            // 64-bit immediate only (9 bytes)
+            Opcode::JSUBI => 9, // 1 (opcode) + 8 (immediate)
+
+            // Register(s) + 64-bit immediate (10 bytes)
+            Opcode::LOADI | Opcode::ADDI => 10, // 1 (opcode) + 1 (regs) + 8 (immediate)
         }
     }
+
+    /// Whether the instruction always changes state and is rejected in static mode.
+    ///
+    /// CALL only changes state when it sends value, so it is checked at execution.
+    pub fn modifies_state(&self) -> bool {
+        matches!(
+            self,
+            Opcode::SSTORE
+                | Opcode::SSTOREM
+                | Opcode::TSTORE
+                | Opcode::TRANSFER
+                | Opcode::CREATE
+                | Opcode::LOG
+                | Opcode::LOG0
+                | Opcode::LOG1
+                | Opcode::LOG2
+                | Opcode::LOG3
+                | Opcode::LOG4
+        )
+    }
 }
@@ -0,0 +1,236 @@
+//! Gas profiler.
+//!
+//! [`GasProfiler`] is an [`Inspector`] that attributes gas to opcodes,
+//! program counters and assembler labels. Gas spent inside a nested call is
+//! charged to the callee's instructions, not to the CALL that started it.
+
+use crate::executor::{Vm, VmError};
+use crate::inspector::Inspector;
+use crate::opcodes::Opcode;
+use minichain_core::Address;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Number of program counters listed in [`GasProfiler::table`].
+const TABLE_TOP_PCS: usize = 20;
+
+/// Executions and gas attributed to one profile entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasStat {
+    pub count: u64,
+    pub gas: u64,
+}
+
+impl GasStat {
+    fn add(&mut self, gas: u64) {
+        self.count += 1;
+        self.gas += gas;
+    }
+}
+
+/// An instruction that has started but not finished.
+struct OpenStep {
+    depth: usize,
+    address: Address,
+    pc: usize,
+    opcode: Option<Opcode>,
+    frame: String,
+    gas_before: u64,
+    child_gas: u64,
+}
+
+/// Collects gas usage per opcode, PC and label.
+#[derive(Default)]
+pub struct GasProfiler {
+    labels: HashMap<Address, Vec<(usize, String)>>,
+    open: Vec<OpenStep>,
+    total: GasStat,
+    by_opcode: HashMap<Opcode, GasStat>,
+    by_pc: HashMap<(Address, usize), GasStat>,
+    by_label: HashMap<String, GasStat>,
+    folded: HashMap<String, u64>,
+}
+
+impl GasProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attribute gas in `address`'s code to the labels of its source map.
+    ///
+    /// Code without labels is attributed to its contract address.
+    pub fn with_labels(mut self, address: Address, labels: HashMap<String, usize>) -> Self {
+        let mut labels: Vec<(usize, String)> =
+            labels.into_iter().map(|(name, pc)| (pc, name)).collect();
+        labels.sort();
+        self.labels.insert(address, labels);
+        self
+    }
+
+    /// Gas and step count over all profiled instructions.
+    pub fn total(&self) -> GasStat {
+        self.total
+    }
+
+    /// Gas per opcode, most expensive first.
+    pub fn by_opcode(&self) -> Vec<(Opcode, GasStat)> {
+        sorted(&self.by_opcode, |opcode| *opcode as u8)
+    }
+
+    /// Gas per contract and PC, most expensive first.
+    pub fn by_pc(&self) -> Vec<((Address, usize), GasStat)> {
+        sorted(&self.by_pc, |(address, pc)| (address.0, *pc))
+    }
+
+    /// Gas per label (or contract address for unlabeled code), most expensive first.
+    pub fn by_label(&self) -> Vec<(String, GasStat)> {
+        sorted(&self.by_label, |label| label.clone())
+    }
+
+    /// Human-readable report of gas by label, opcode and the hottest PCs.
+    pub fn table(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Gas profile: {} gas in {} steps",
+            self.total.gas, self.total.count
+        );
+
+        self.write_section(&mut out, "Label", "Steps", self.by_label());
+        self.write_section(
+            &mut out,
+            "Opcode",
+            "Count",
+            self.by_opcode()
+                .into_iter()
+                .map(|(opcode, stat)| (format!("{opcode:?}"), stat))
+                .collect(),
+        );
+        self.write_section(
+            &mut out,
+            "PC",
+            "Count",
+            self.by_pc()
+                .into_iter()
+                .take(TABLE_TOP_PCS)
+                .map(|((address, pc), stat)| {
+                    (format!("{pc:#06x} {}", self.frame_name(&address, pc)), stat)
+                })
+                .collect(),
+        );
+        out
+    }
+
+    /// Folded stacks (`frame;frame;OPCODE gas` per line) for flamegraph tools.
+    ///
+    /// Each frame is the label a call frame is executing in, outermost first.
+    pub fn folded(&self) -> String {
+        let mut lines: Vec<_> = self.folded.iter().collect();
+        lines.sort();
+        lines
+            .into_iter()
+            .map(|(stack, gas)| format!("{stack} {gas}\n"))
+            .collect()
+    }
+
+    fn write_section(
+        &self,
+        out: &mut String,
+        title: &str,
+        unit: &str,
+        rows: Vec<(String, GasStat)>,
+    ) {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{title:<32} {:>12} {:>7} {unit:>8}", "Gas", "%");
+        for (name, stat) in rows {
+            let share = if self.total.gas == 0 {
+                0.0
+            } else {
+                stat.gas as f64 * 100.0 / self.total.gas as f64
+            };
+            let _ = writeln!(
+                out,
+                "{name:<32} {:>12} {share:>6.1}% {:>8}",
+                stat.gas, stat.count
+            );
+        }
+    }
+
+    /// Attribute the gas of the innermost open step of `vm`'s frame.
+    fn close_step(&mut self, vm: &Vm<'_>) {
+        let step = loop {
+            match self.open.pop() {
+                Some(step) if step.depth == vm.depth() => break step,
+                Some(_) => continue,
+                None => return,
+            }
+        };
+        let Some(opcode) = step.opcode else {
+            return;
+        };
+
+        let cost = step.gas_before.saturating_sub(vm.gas_remaining());
+        let gas = cost.saturating_sub(step.child_gas);
+        if let Some(caller) = self.open.last_mut() {
+            caller.child_gas += cost;
+        }
+
+        self.total.add(gas);
+        self.by_opcode.entry(opcode).or_default().add(gas);
+        self.by_pc
+            .entry((step.address, step.pc))
+            .or_default()
+            .add(gas);
+        self.by_label
+            .entry(step.frame.clone())
+            .or_default()
+            .add(gas);
+
+        let mut stack: Vec<&str> = self.open.iter().map(|s| s.frame.as_str()).collect();
+        stack.push(&step.frame);
+        let key = format!("{};{opcode:?}", stack.join(";"));
+        *self.folded.entry(key).or_default() += gas;
+    }
+
+    /// Label covering `pc`, or the contract address if there is none.
+    fn frame_name(&self, address: &Address, pc: usize) -> String {
+        self.labels
+            .get(address)
+            .and_then(|labels| labels.iter().rev().find(|(start, _)| *start <= pc))
+            .map_or_else(|| address.to_hex(), |(_, name)| name.clone())
+    }
+}
+
+impl Inspector for GasProfiler {
+    fn step(&mut self, vm: &Vm<'_>) {
+        self.open.push(OpenStep {
+            depth: vm.depth(),
+            address: *vm.address(),
+            pc: vm.pc(),
+            opcode: vm.current_opcode(),
+            frame: self.frame_name(vm.address(), vm.pc()),
+            gas_before: vm.gas_remaining(),
+            child_gas: 0,
+        });
+    }
+
+    fn step_end(&mut self, vm: &Vm<'_>) {
+        self.close_step(vm);
+    }
+
+    fn step_error(&mut self, vm: &Vm<'_>, _error: &VmError) {
+        // The failing instruction still spent gas, e.g. the fixed charge of
+        // an instruction that then ran out of gas, and the receipt counts it.
+        self.close_step(vm);
+    }
+}
+
+/// Entries of `stats`, most gas first, ties broken by `order` for stable output.
+fn sorted<K: Clone, T: Ord>(
+    stats: &HashMap<K, GasStat>,
+    order: impl Fn(&K) -> T,
+) -> Vec<(K, GasStat)> {
+    let mut entries: Vec<_> = stats.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    entries.sort_by(|(ka, a), (kb, b)| b.gas.cmp(&a.gas).then_with(|| order(ka).cmp(&order(kb))));
+    entries
+}
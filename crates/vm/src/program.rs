@@ -0,0 +1,234 @@
+//! Decode-once program representation.
+//!
+//! [`Program`] decodes bytecode into fixed-size [`Instruction`]s up front, so
+//! the interpreter no longer unpacks operand nibbles on every step. Programs
+//! are immutable and shared through `Arc`; a [`ProgramCache`] keyed by code
+//! hash lets repeated calls to the same contract skip decoding entirely.
+
+use crate::opcodes::Opcode;
+use minichain_core::Hash;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Marks byte offsets that do not start an instruction.
+const NOT_AN_INSTRUCTION: u32 = u32::MAX;
+
+/// Most register operands any instruction takes (CALL).
+const MAX_OPERANDS: usize = 8;
+
+/// A decoded instruction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Instruction {
+    /// The opcode, or `None` for an unknown byte.
+    pub opcode: Option<Opcode>,
+    /// Raw opcode byte, kept for error reporting.
+    pub byte: u8,
+    /// Register operands, packed high nibble first after the opcode byte.
+    pub regs: [u8; MAX_OPERANDS],
+    /// 64-bit immediate of LOADI, ADDI and JSUBI.
+    pub imm: u64,
+}
+
+impl Instruction {
+    /// Decode the instruction at `offset`. Bytes past the end of the code read as zero.
+    fn decode(code: &[u8], offset: usize) -> (Self, usize) {
+        let byte = code[offset];
+        let opcode = Opcode::from_byte(byte);
+        let size = opcode.map_or(1, |op| op.instruction_size());
+        let operand = |i: usize| code.get(offset + i).copied().unwrap_or(0);
+
+        let regs = std::array::from_fn(|i| {
+            let packed = operand(1 + i / 2);
+            if i % 2 == 0 {
+                packed >> 4
+            } else {
+                packed & 0x0F
+            }
+        });
+        let imm = match opcode {
+            Some(Opcode::LOADI | Opcode::ADDI) => {
+                u64::from_le_bytes(std::array::from_fn(|i| operand(2 + i)))
+            }
+            // JSUBI has no register byte; its immediate follows the opcode.
+            Some(Opcode::JSUBI) => u64::from_le_bytes(std::array::from_fn(|i| operand(1 + i))),
+            _ => 0,
+        };
+
+        (
+            Self {
+                opcode,
+                byte,
+                regs,
+                imm,
+            },
+            size,
+        )
+    }
+}
+
+/// Bytecode decoded into instructions, with a PC → instruction jump table.
+#[derive(Debug)]
+pub struct Program {
+    code: Vec<u8>,
+    instructions: Vec<Instruction>,
+    /// Instruction index for every byte offset, or [`NOT_AN_INSTRUCTION`].
+    index: Vec<u32>,
+}
+
+impl Program {
+    /// Decode `code`. Unknown opcodes decode as one-byte instructions that fail when executed.
+    pub fn new(code: Vec<u8>) -> Self {
+        let mut instructions = Vec::new();
+        let mut index = vec![NOT_AN_INSTRUCTION; code.len()];
+        let mut offset = 0;
+        while offset < code.len() {
+            let (instruction, size) = Instruction::decode(&code, offset);
+            index[offset] = instructions.len() as u32;
+            instructions.push(instruction);
+            offset += size;
+        }
+        Self {
+            code,
+            instructions,
+            index,
+        }
+    }
+
+    /// The raw bytecode.
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    /// Length of the bytecode in bytes.
+    pub fn len(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+
+    /// The instruction starting at `pc`, if one does.
+    pub fn instruction_at(&self, pc: usize) -> Option<&Instruction> {
+        match self.index.get(pc) {
+            Some(&i) if i != NOT_AN_INSTRUCTION => Some(&self.instructions[i as usize]),
+            _ => None,
+        }
+    }
+
+    /// Whether `pc` is a valid jump destination.
+    pub fn is_jump_target(&self, pc: usize) -> bool {
+        self.instruction_at(pc).is_some()
+    }
+
+    /// Instructions in code order, with the offset each one starts at.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Instruction)> {
+        self.index
+            .iter()
+            .enumerate()
+            .filter(|(_, &i)| i != NOT_AN_INSTRUCTION)
+            .map(|(offset, &i)| (offset, &self.instructions[i as usize]))
+    }
+}
+
+/// Decoded programs shared across executions, keyed by code hash.
+///
+/// Entries are never evicted: contract code is immutable and the number of
+/// distinct contracts on a chain is small.
+#[derive(Debug, Default)]
+pub struct ProgramCache {
+    programs: Mutex<HashMap<Hash, Arc<Program>>>,
+}
+
+impl ProgramCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached program for `code_hash`, if any.
+    pub fn get(&self, code_hash: &Hash) -> Option<Arc<Program>> {
+        self.programs
+            .lock()
+            .expect("program cache lock poisoned")
+            .get(code_hash)
+            .cloned()
+    }
+
+    /// Decode and cache `code` under `code_hash`, returning the shared program.
+    ///
+    /// If another execution cached the same hash first, its program is kept.
+    pub fn insert(&self, code_hash: Hash, code: Vec<u8>) -> Arc<Program> {
+        self.insert_program(code_hash, Program::new(code))
+    }
+
+    /// Cache an already decoded `program` under `code_hash`, returning the shared program.
+    ///
+    /// If another execution cached the same hash first, its program is kept.
+    pub fn insert_program(&self, code_hash: Hash, program: Program) -> Arc<Program> {
+        let program = Arc::new(program);
+        self.programs
+            .lock()
+            .expect("program cache lock poisoned")
+            .entry(code_hash)
+            .or_insert(program)
+            .clone()
+    }
+
+    /// Number of cached programs.
+    pub fn len(&self) -> usize {
+        self.programs
+            .lock()
+            .expect("program cache lock poisoned")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minichain_core::hash;
+
+    #[test]
+    fn test_decode_operands_and_jump_table() {
+        // LOADI R3, 500 / ADD R1, R2, R3 / 0xEE / LOADI R0 (truncated)
+        let mut code = vec![0x70, 0x30];
+        code.extend_from_slice(&500u64.to_le_bytes());
+        code.extend_from_slice(&[0x10, 0x12, 0x30, 0xEE, 0x70, 0x00, 0x01]);
+        let program = Program::new(code);
+
+        let loadi = program.instruction_at(0).unwrap();
+        assert_eq!(loadi.opcode, Some(Opcode::LOADI));
+        assert_eq!(loadi.regs[0], 3);
+        assert_eq!(loadi.imm, 500);
+
+        let add = program.instruction_at(10).unwrap();
+        assert_eq!(add.opcode, Some(Opcode::ADD));
+        assert_eq!(&add.regs[..3], &[1, 2, 3]);
+
+        let unknown = program.instruction_at(13).unwrap();
+        assert_eq!((unknown.opcode, unknown.byte), (None, 0xEE));
+
+        assert_eq!(program.instruction_at(14).unwrap().imm, 1);
+        assert!(!program.is_jump_target(2));
+        assert!(!program.is_jump_target(11));
+        assert!(!program.is_jump_target(program.len()));
+    }
+
+    #[test]
+    fn test_cache_shares_programs_by_hash() {
+        let cache = ProgramCache::new();
+        let code = vec![0x01, 0x00];
+        let code_hash = hash(&code);
+
+        assert!(cache.get(&code_hash).is_none());
+        let first = cache.insert(code_hash, code.clone());
+        let second = cache.insert(code_hash, code);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(&first, &cache.get(&code_hash).unwrap()));
+        assert_eq!(cache.len(), 1);
+    }
+}
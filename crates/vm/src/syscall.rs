@@ -0,0 +1,30 @@
+//! Embedder-defined host functions.
+//!
+//! `SYSCALL dst, id, off, len, out` calls host function `id` with the
+//! `len` bytes of memory at `off`. The function's data is written to memory
+//! at `out` and its scalar result to `dst`. This lets the embedder add
+//! capabilities without new opcodes.
+
+use crate::executor::Vm;
+
+/// What a host function returns to the contract.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyscallOutput {
+    /// Written to the destination register.
+    pub value: u64,
+    /// Written to memory at the output offset.
+    pub data: Vec<u8>,
+}
+
+/// Functions the embedder exposes through SYSCALL, selected by numeric id.
+pub trait HostFunctions {
+    /// Gas charged for calling `id` with `input`, or `None` if there is no
+    /// function `id`.
+    fn gas(&self, id: u64, input: &[u8]) -> Option<u64>;
+
+    /// Run function `id` for the frame `vm`.
+    ///
+    /// Functions that change state should fail when `vm.is_static()`.
+    /// An error aborts the frame with [`VmError::SyscallFailed`](crate::VmError::SyscallFailed).
+    fn call(&self, vm: &Vm<'_>, id: u64, input: &[u8]) -> Result<SyscallOutput, String>;
+}
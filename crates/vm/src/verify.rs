@@ -0,0 +1,91 @@
+//! Static bytecode verification.
+//!
+//! Decodes bytecode into a [`Program`] and walks its instructions,
+//! rejecting unknown opcodes and instructions cut off by the end of the code.
+
+use crate::opcodes::Opcode;
+use crate::program::Program;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    #[error("Unknown opcode 0x{opcode:02X} at offset {offset}")]
+    UnknownOpcode { offset: usize, opcode: u8 },
+
+    #[error("Truncated {opcode:?} at offset {offset}: needs {size} bytes, {available} available")]
+    TruncatedInstruction {
+        offset: usize,
+        opcode: Opcode,
+        size: usize,
+        available: usize,
+    },
+}
+
+/// Check that `bytecode` decodes into whole, known instructions.
+///
+/// Returns the decoded [`Program`], whose instruction starts are the only
+/// valid jump destinations.
+pub fn verify(bytecode: &[u8]) -> Result<Program, VerifyError> {
+    let program = Program::new(bytecode.to_vec());
+    for (offset, instruction) in program.iter() {
+        let opcode = instruction.opcode.ok_or(VerifyError::UnknownOpcode {
+            offset,
+            opcode: instruction.byte,
+        })?;
+        let size = opcode.instruction_size();
+        let available = bytecode.len() - offset;
+        if size > available {
+            return Err(VerifyError::TruncatedInstruction {
+                offset,
+                opcode,
+                size,
+                available,
+            });
+        }
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_marks_instruction_boundaries() {
+        // LOADI R0, 11 / JUMP R0 / HALT
+        let mut code = vec![0x70, 0x00];
+        code.extend_from_slice(&11u64.to_le_bytes());
+        code.extend_from_slice(&[0x02, 0x00, 0x00]);
+
+        let program = verify(&code).unwrap();
+        assert!(program.is_jump_target(0));
+        assert!(!program.is_jump_target(2));
+        assert!(program.is_jump_target(10));
+        assert!(program.is_jump_target(12));
+        assert!(!program.is_jump_target(13));
+    }
+
+    #[test]
+    fn test_verify_rejects_unknown_opcode() {
+        assert_eq!(
+            verify(&[0x00, 0xEE]).err(),
+            Some(VerifyError::UnknownOpcode {
+                offset: 1,
+                opcode: 0xEE
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_truncated_instruction() {
+        assert_eq!(
+            verify(&[0x00, 0x70, 0x00, 0x01]).err(),
+            Some(VerifyError::TruncatedInstruction {
+                offset: 1,
+                opcode: Opcode::LOADI,
+                size: 10,
+                available: 3,
+            })
+        );
+    }
+}
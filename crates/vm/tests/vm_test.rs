@@ -1,5 +1,13 @@
 use minichain_core::crypto::Address;
-use minichain_vm::Vm;
+use minichain_core::{hash, Keypair};
+use minichain_vm::{
+    BlockContext, Debugger, ExecutionStatus, GasCosts, GasSchedule, GasSchedules, HostFunctions,
+    Inspector, JsonTracer, Opcode, OpcodeCounter, StopReason, StorageBackend, StorageDiffInspector,
+    SyscallOutput, TxContext, Vm, VmError, Watchpoint,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 #[test]
 fn test_add() {
@@ -13,7 +21,14 @@ fn test_add() {
         0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x20, 0x10, 0xF0, 0x20, 0x00,
     ];
 
-    let mut vm = Vm::new(bytecode, 1_000_000, Address::ZERO, Address::ZERO, 0);
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
     let result = vm.run().unwrap();
 
     assert!(result.success);
@@ -32,7 +47,14 @@ fn test_sub() {
         0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x20, 0x10, 0xF0, 0x20, 0x00,
     ];
 
-    let mut vm = Vm::new(bytecode, 1_000_000, Address::ZERO, Address::ZERO, 0);
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
     let result = vm.run().unwrap();
 
     assert!(result.success);
@@ -51,7 +73,14 @@ fn test_mul() {
         0x00, 0x00, 0x00, 0x00, 0x00, 0x12, 0x20, 0x10, 0xF0, 0x20, 0x00,
     ];
 
-    let mut vm = Vm::new(bytecode, 1_000_000, Address::ZERO, Address::ZERO, 0);
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
     let result = vm.run().unwrap();
 
     assert!(result.success);
@@ -70,7 +99,14 @@ fn test_div() {
         0x00, 0x00, 0x00, 0x00, 0x00, 0x13, 0x20, 0x10, 0xF0, 0x20, 0x00,
     ];
 
-    let mut vm = Vm::new(bytecode, 1_000_000, Address::ZERO, Address::ZERO, 0);
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
     let result = vm.run().unwrap();
 
     assert!(result.success);
@@ -89,7 +125,14 @@ fn test_bitwise_and() {
         0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x20, 0x10, 0xF0, 0x20, 0x00,
     ];
 
-    let mut vm = Vm::new(bytecode, 1_000_000, Address::ZERO, Address::ZERO, 0);
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
     let result = vm.run().unwrap();
 
     assert!(result.success);
@@ -108,7 +151,14 @@ fn test_comparison_lt() {
         0x00, 0x00, 0x00, 0x00, 0x00, 0x32, 0x20, 0x10, 0xF0, 0x20, 0x00,
     ];
 
-    let mut vm = Vm::new(bytecode, 1_000_000, Address::ZERO, Address::ZERO, 0);
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
     let result = vm.run().unwrap();
 
     assert!(result.success);
@@ -125,7 +175,14 @@ fn test_mov() {
         0x70, 0x00, 0x2A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x71, 0x10, 0xF0, 0x10, 0x00,
     ];
 
-    let mut vm = Vm::new(bytecode, 1_000_000, Address::ZERO, Address::ZERO, 0);
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
     let result = vm.run().unwrap();
 
     assert!(result.success);
@@ -140,8 +197,880 @@ fn test_out_of_gas() {
     ];
 
     // Gas limit of 1 is not enough for LOADI (costs 2)
-    let mut vm = Vm::new(bytecode, 1, Address::ZERO, Address::ZERO, 0);
+    let mut vm = Vm::new(
+        bytecode,
+        1,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
     let result = vm.run();
 
     assert!(result.is_err());
 }
+
+#[test]
+fn test_return_memory_range() {
+    // LOADI R0, 8
+    // LOADI R1, 16
+    // LOADI R2, 7
+    // STORE64 R0, R2
+    // RETURN R0, R1
+    let bytecode = vec![
+        0x70, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x70, 0x10, 0x10, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x70, 0x20, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x43, 0x02, 0x06, 0x01,
+    ];
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    let result = vm.run().unwrap();
+
+    // Bytes past the end of memory are zero-padded.
+    let mut expected = vec![0u8; 16];
+    expected[0] = 7;
+    assert!(result.success);
+    assert_eq!(result.status, ExecutionStatus::Returned);
+    assert_eq!(result.return_data, expected);
+}
+
+#[test]
+fn test_revert_with_data() {
+    // LOADI R1, 0x6F6E ("no")
+    // STORE64 R0, R1
+    // LOADI R2, 2
+    // REVERT R0, R2
+    let bytecode = vec![
+        0x70, 0x10, 0x6E, 0x6F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x01, 0x70, 0x20, 0x02,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0E, 0x02,
+    ];
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    assert_eq!(vm.run().err(), Some(VmError::Reverted(b"no".to_vec())));
+}
+
+#[test]
+fn test_execution_status() {
+    let run = |bytecode: Vec<u8>| {
+        let mut vm = Vm::new(
+            bytecode,
+            1_000_000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            GasSchedule::default(),
+        );
+        vm.run().unwrap()
+    };
+
+    let halted = run([loadi(0, 1), vec![0x00]].concat());
+    assert_eq!(halted.status, ExecutionStatus::Halted);
+    assert!(halted.success);
+
+    let fell_off = run(loadi(0, 1));
+    assert_eq!(fell_off.status, ExecutionStatus::FellOffEnd);
+    assert!(!fell_off.success);
+
+    assert_eq!(
+        ExecutionStatus::from(VmError::InvalidOpcode(0xEE)),
+        ExecutionStatus::InvalidOpcode(0xEE)
+    );
+    let reverted = ExecutionStatus::from(VmError::Reverted(b"no".to_vec()));
+    let json = serde_json::to_value(&reverted).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"kind": "reverted", "data": "6e6f"})
+    );
+    assert_eq!(
+        serde_json::from_value::<ExecutionStatus>(json).unwrap(),
+        reverted
+    );
+    assert_eq!(
+        serde_json::to_value(ExecutionStatus::OutOfGas).unwrap(),
+        serde_json::json!({"kind": "out_of_gas"})
+    );
+}
+
+#[test]
+fn test_return_rejects_oversized_data() {
+    // LOADI R1, 64
+    // RETURN R0, R1
+    let bytecode = vec![
+        0x70, 0x10, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x06, 0x01,
+    ];
+
+    let mut vm = Vm::new(
+        bytecode.clone(),
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    assert_eq!(vm.run().unwrap().return_data.len(), 64);
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    vm.set_max_return_size(32);
+    assert_eq!(
+        vm.run().err(),
+        Some(VmError::ReturnDataTooLarge { size: 64, max: 32 })
+    );
+}
+
+#[test]
+fn test_log2_emits_event() {
+    // LOADI R1, 8
+    // LOADI R2, 5
+    // STORE64 R0, R2
+    // LOADI R3, 0xAA
+    // LOADI R4, 0xBB
+    // LOG2 R0, R1, R3, R4
+    // HALT
+    let bytecode = vec![
+        0x70, 0x10, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x70, 0x20, 0x05, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x02, 0x70, 0x30, 0xAA, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x70, 0x40, 0xBB, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xA2, 0x01, 0x34,
+        0x00,
+    ];
+
+    let contract = Address([0x11; 20]);
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        contract,
+        0,
+        GasSchedule::default(),
+    );
+    let result = vm.run().unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.events.len(), 1);
+    assert_eq!(result.events[0].address, contract);
+    assert_eq!(result.events[0].topics, vec![0xAA, 0xBB]);
+    assert_eq!(result.events[0].data, 5u64.to_le_bytes().to_vec());
+}
+
+#[test]
+fn test_hash_memory_range() {
+    // LOADI R1, 8
+    // LOADI R2, 42
+    // STORE64 R0, R2
+    // HASH64 R3, R0, R1
+    // LOG R3
+    // LOADI R4, 64
+    // LOADI R5, 32
+    // HASH R4, R0, R1
+    // RETURN R4, R5
+    let bytecode = vec![
+        0x70, 0x10, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x70, 0x20, 0x2A, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x43, 0x02, 0x61, 0x30, 0x10, 0xF0, 0x30, 0x70, 0x40, 0x40,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x70, 0x50, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x60, 0x40, 0x10, 0x06, 0x45,
+    ];
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    let result = vm.run().unwrap();
+
+    let digest = hash(&42u64.to_le_bytes());
+    assert!(result.success);
+    assert_eq!(result.return_data, digest.0.to_vec());
+    assert_eq!(
+        result.logs,
+        vec![u64::from_be_bytes(digest.0[24..32].try_into().unwrap())]
+    );
+}
+
+#[test]
+fn test_full_address_comparison() {
+    // LOADI R1, 32
+    // CALLERM R0
+    // ADDRESSM R1
+    // ADDREQ R2, R0, R1
+    // LOG R2
+    // CALLER R3
+    // ADDRESS R4
+    // EQ R5, R3, R4
+    // LOG R5
+    // HALT
+    let bytecode = vec![
+        0x70, 0x10, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x86, 0x00, 0x87, 0x10, 0x88,
+        0x20, 0x10, 0xF0, 0x20, 0x80, 0x30, 0x82, 0x40, 0x30, 0x53, 0x40, 0xF0, 0x50, 0x00,
+    ];
+
+    // Two addresses that share their first 8 bytes.
+    let mut caller = [0x01; 20];
+    caller[8..].fill(0x02);
+    let mut contract = [0x01; 20];
+    contract[8..].fill(0x03);
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address(caller),
+        Address(contract),
+        0,
+        GasSchedule::default(),
+    );
+    let result = vm.run().unwrap();
+
+    assert!(result.success);
+    // Full comparison tells them apart; the 8-byte register form cannot.
+    assert_eq!(result.logs, vec![0, 1]);
+}
+
+#[test]
+fn test_sigverify() {
+    // CALLDATASIZE R5
+    // CALLDATACOPY R1, R1, R5
+    // LOADI R2, 96
+    // LOADI R3, 5
+    // LOADI R4, 32
+    // SIGVERIFY R0, R1, R2, R3, R4
+    // LOG R0
+    // HALT
+    let bytecode = vec![
+        0x46, 0x50, 0x48, 0x11, 0x50, 0x70, 0x20, 0x60, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x70, 0x30, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x70, 0x40, 0x20, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x62, 0x01, 0x23, 0x40, 0xF0, 0x00, 0x00,
+    ];
+
+    // Calldata layout: public key at 0, signature at 32, message at 96.
+    let keypair = Keypair::generate();
+    let signature = keypair.sign(b"hello");
+    let mut calldata = keypair.public_key.as_bytes().to_vec();
+    calldata.extend_from_slice(signature.as_bytes());
+
+    for (message, expected) in [(b"hello", 1), (b"hellp", 0)] {
+        let mut input = calldata.clone();
+        input.extend_from_slice(message);
+
+        let mut vm = Vm::new(
+            bytecode.clone(),
+            1_000_000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            GasSchedule::default(),
+        );
+        vm.set_calldata(input);
+        let result = vm.run().unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.logs, vec![expected]);
+    }
+}
+
+#[test]
+fn test_calldata_instructions() {
+    // CALLDATASIZE R0
+    // LOG R0
+    // LOADI R1, 8
+    // CALLDATALOAD R2, R1
+    // LOG R2
+    // LOADI R5, 100
+    // CALLDATACOPY R5, R1, R1
+    // LOAD64 R6, R5
+    // LOG R6
+    // HALT
+    let bytecode = vec![
+        0x46, 0x00, 0xF0, 0x00, 0x70, 0x10, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x47,
+        0x21, 0xF0, 0x20, 0x70, 0x50, 0x64, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x48, 0x51,
+        0x10, 0x41, 0x65, 0xF0, 0x60, 0x00,
+    ];
+
+    // Ten bytes of calldata: reads past the end are zero-padded.
+    let mut calldata = 7u64.to_le_bytes().to_vec();
+    calldata.extend_from_slice(&[0xBB, 0xAA]);
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    vm.set_calldata(calldata);
+    let result = vm.run().unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.logs, vec![10, 0xAABB, 0xAABB]);
+}
+
+#[test]
+fn test_memory_expansion_is_charged() {
+    // LOADI R0, 1000
+    // STORE8 R0, R0
+    // HALT
+    let bytecode = vec![
+        0x70, 0x00, 0xE8, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x42, 0x00, 0x00,
+    ];
+    let gas_used = |schedule: GasSchedule| {
+        let mut vm = Vm::new(
+            bytecode.clone(),
+            1_000_000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            schedule,
+        );
+        vm.run().unwrap().gas_used
+    };
+
+    let free_memory = GasSchedule {
+        memory_grow_per_byte: 0,
+        ..GasSchedule::default()
+    };
+
+    // Writing byte 1000 grows memory to 1001 bytes.
+    assert_eq!(
+        gas_used(GasSchedule::default()) - gas_used(free_memory),
+        1001
+    );
+}
+
+#[test]
+fn test_opcode_override_at_activation_height() {
+    // LOADI R0, 6
+    // LOADI R1, 7
+    // MUL R2, R0, R1
+    // MULHI R3, R0, R1
+    // HALT
+    let mut bytecode = [loadi(0, 6), loadi(1, 7)].concat();
+    bytecode.extend_from_slice(&[0x12, 0x20, 0x10, 0x1B, 0x30, 0x10, 0x00]);
+
+    let schedules = GasSchedules::default()
+        .with_activation(100, GasSchedule::default().with_opcode(Opcode::MUL, 50));
+    let gas_at = |height| {
+        let mut vm = Vm::new(
+            bytecode.clone(),
+            1_000_000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            schedules.at(height),
+        );
+        vm.run().unwrap().gas_used
+    };
+
+    // Only MUL is repriced; MULHI keeps the price of its tier.
+    assert_eq!(gas_at(100) - gas_at(99), 50 - GasCosts::LOW);
+}
+
+/// Encode `LOADI reg, value`.
+fn loadi(reg: u8, value: u64) -> Vec<u8> {
+    let mut bytes = vec![0x70, reg << 4];
+    bytes.extend_from_slice(&value.to_le_bytes());
+    bytes
+}
+
+/// Run `OP R2, R0, R1` with R0 = `a` and R1 = `b`, returning R2.
+fn run_binary(opcode: u8, a: u64, b: u64) -> Result<u64, VmError> {
+    let mut bytecode = [loadi(0, a), loadi(1, b)].concat();
+    // OP R2, R0, R1 / LOG R2 / HALT
+    bytecode.extend_from_slice(&[opcode, 0x20, 0x10, 0xF0, 0x20, 0x00]);
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    vm.run().map(|result| result.logs[0])
+}
+
+#[test]
+fn test_signed_division() {
+    const SDIV: u8 = 0x16;
+    const SMOD: u8 = 0x17;
+    let neg = |v: i64| v as u64;
+
+    assert_eq!(run_binary(SDIV, neg(-7), 2), Ok(neg(-3)));
+    assert_eq!(run_binary(SMOD, neg(-7), 2), Ok(neg(-1)));
+    assert_eq!(run_binary(SDIV, neg(i64::MIN), neg(-1)), Ok(neg(i64::MIN)));
+    assert_eq!(run_binary(SDIV, 1, 0), Err(VmError::DivisionByZero));
+}
+
+#[test]
+fn test_signed_comparison_and_shift() {
+    const LT: u8 = 0x32;
+    const SAR: u8 = 0x26;
+    const SLT: u8 = 0x37;
+    const SGT: u8 = 0x38;
+    let neg = |v: i64| v as u64;
+
+    assert_eq!(run_binary(LT, neg(-1), 1), Ok(0));
+    assert_eq!(run_binary(SLT, neg(-1), 1), Ok(1));
+    assert_eq!(run_binary(SGT, 1, neg(-1)), Ok(1));
+    assert_eq!(run_binary(SAR, neg(-16), 2), Ok(neg(-4)));
+}
+
+#[test]
+fn test_checked_arithmetic() {
+    const ADDC: u8 = 0x18;
+    const SUBC: u8 = 0x19;
+    const MULC: u8 = 0x1A;
+    const MULHI: u8 = 0x1B;
+
+    assert_eq!(run_binary(ADDC, 2, 3), Ok(5));
+    assert_eq!(
+        run_binary(ADDC, u64::MAX, 1),
+        Err(VmError::ArithmeticOverflow)
+    );
+    assert_eq!(run_binary(SUBC, 0, 1), Err(VmError::ArithmeticOverflow));
+    assert_eq!(
+        run_binary(MULC, 1 << 32, 1 << 32),
+        Err(VmError::ArithmeticOverflow)
+    );
+    assert_eq!(run_binary(MULHI, 1 << 63, 4), Ok(2));
+    assert_eq!(run_binary(MULHI, u64::MAX, u64::MAX), Ok(u64::MAX - 1));
+}
+
+#[test]
+fn test_jump_into_immediate_is_rejected() {
+    // LOADI R0, 2 (the middle of this LOADI)
+    // JUMP R0
+    let mut bytecode = loadi(0, 2);
+    bytecode.extend_from_slice(&[0x02, 0x00, 0x00]);
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    assert_eq!(vm.run().err(), Some(VmError::InvalidJump(2)));
+}
+
+#[test]
+fn test_addi_uses_64_bit_immediate() {
+    // LOADI R0, 1
+    // ADDI R1, R0, 1 << 40
+    // LOG R1
+    // HALT
+    let mut bytecode = loadi(0, 1);
+    bytecode.extend_from_slice(&[0x15, 0x10]);
+    bytecode.extend_from_slice(&(1u64 << 40).to_le_bytes());
+    bytecode.extend_from_slice(&[0xF0, 0x10, 0x00]);
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    assert_eq!(vm.run().unwrap().logs, vec![(1 << 40) + 1]);
+}
+
+#[derive(Default)]
+struct MapStorage {
+    slots: HashMap<[u8; 32], [u8; 32]>,
+    transient: HashMap<[u8; 32], [u8; 32]>,
+}
+
+impl StorageBackend for MapStorage {
+    fn sload(&self, key: &[u8; 32]) -> [u8; 32] {
+        self.slots.get(key).copied().unwrap_or([0u8; 32])
+    }
+
+    fn sstore(&mut self, key: &[u8; 32], value: &[u8; 32]) {
+        self.slots.insert(*key, *value);
+    }
+
+    fn tload(&self, key: &[u8; 32]) -> [u8; 32] {
+        self.transient.get(key).copied().unwrap_or([0u8; 32])
+    }
+
+    fn tstore(&mut self, key: &[u8; 32], value: &[u8; 32]) {
+        self.transient.insert(*key, *value);
+    }
+}
+
+#[test]
+fn test_debugger_storage_watchpoint() {
+    // LOADI R0, 3
+    // LOADI R1, 99
+    // SSTORE R0, R1
+    // HALT
+    let mut bytecode = loadi(0, 3);
+    bytecode.extend(loadi(1, 99));
+    bytecode.extend_from_slice(&[0x51, 0x01, 0x00]);
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    vm.set_storage(Box::new(MapStorage::default()));
+
+    let mut slot = [0u8; 32];
+    slot[31] = 3;
+    let mut value = [0u8; 32];
+    value[31] = 99;
+
+    let mut dbg = Debugger::new(vm);
+    dbg.add_watchpoint(Watchpoint::Storage(slot));
+    let gas_before = dbg.vm().gas_used();
+
+    assert_eq!(
+        dbg.resume().unwrap(),
+        StopReason::Watchpoint {
+            watchpoint: Watchpoint::Storage(slot),
+            old: vec![0u8; 32],
+            new: value.to_vec(),
+        }
+    );
+    assert_eq!(dbg.vm().pc(), 22);
+    assert!(dbg.vm().gas_used() > gas_before);
+    assert_eq!(dbg.vm().storage_writes().get(&slot), Some(&value));
+    assert_eq!(dbg.resume().unwrap(), StopReason::Finished);
+}
+
+#[test]
+fn test_inspectors() {
+    // LOADI R0, 3
+    // LOADI R1, 99
+    // SSTORE R0, R1
+    // SSTORE R0, R0
+    // HALT
+    let mut bytecode = loadi(0, 3);
+    bytecode.extend(loadi(1, 99));
+    bytecode.extend_from_slice(&[0x51, 0x01, 0x51, 0x00, 0x00]);
+
+    let tracer = Rc::new(RefCell::new(JsonTracer::new()));
+    let counter = Rc::new(RefCell::new(OpcodeCounter::new()));
+    let diff = Rc::new(RefCell::new(StorageDiffInspector::new()));
+
+    for inspector in [
+        tracer.clone() as Rc<RefCell<dyn Inspector>>,
+        counter.clone(),
+        diff.clone(),
+    ] {
+        let mut vm = Vm::new(
+            bytecode.clone(),
+            1_000_000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            GasSchedule::default(),
+        );
+        vm.set_storage(Box::new(MapStorage::default()));
+        vm.set_inspector(inspector);
+        assert!(vm.run().unwrap().success);
+    }
+
+    let trace = tracer.borrow().to_json();
+    let steps = trace.as_array().unwrap();
+    assert_eq!(steps.len(), 5);
+    assert_eq!(steps[2]["op"], "SSTORE");
+    assert_eq!(steps[2]["pc"], 20);
+    assert_eq!(steps[2]["registers"][1], 99);
+    assert!(steps[2]["gas_cost"].as_u64().unwrap() > 0);
+
+    let counter = counter.borrow();
+    assert_eq!(counter.count(Opcode::LOADI), 2);
+    assert_eq!(counter.count(Opcode::SSTORE), 2);
+    assert_eq!(counter.total(), 5);
+
+    let mut slot = [0u8; 32];
+    slot[31] = 3;
+    let changes = diff.borrow().diff();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[&(Address::ZERO, slot)].original, [0u8; 32]);
+    assert_eq!(changes[&(Address::ZERO, slot)].current, slot);
+}
+
+#[test]
+fn test_static_mode_rejects_state_changes() {
+    let run_static = |bytecode: Vec<u8>| {
+        let mut vm = Vm::new(
+            bytecode,
+            1_000_000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            GasSchedule::default(),
+        );
+        vm.set_storage(Box::new(MapStorage::default()));
+        vm.set_static(true);
+        vm.run().err()
+    };
+
+    // LOADI R0, 3 / SLOAD R1, R0 / HALT
+    let read = [loadi(0, 3), vec![0x50, 0x10, 0x00]].concat();
+    assert_eq!(run_static(read), None);
+
+    // LOADI R0, 3 / SSTORE R0, R0 / HALT
+    let write = [loadi(0, 3), vec![0x51, 0x00, 0x00]].concat();
+    let err = run_static(write).unwrap();
+    assert_eq!(err, VmError::StaticViolation(Opcode::SSTORE));
+    assert_eq!(ExecutionStatus::from(err), ExecutionStatus::StaticViolation);
+
+    // LOG R0 / HALT
+    assert_eq!(
+        run_static(vec![0xF0, 0x00, 0x00]),
+        Some(VmError::StaticViolation(Opcode::LOG))
+    );
+    // LOG0 R0, R0 / HALT
+    assert_eq!(
+        run_static(vec![0xA0, 0x00, 0x00]),
+        Some(VmError::StaticViolation(Opcode::LOG0))
+    );
+    // TSTORE R0, R0 / HALT
+    assert_eq!(
+        run_static(vec![0x55, 0x00, 0x00]),
+        Some(VmError::StaticViolation(Opcode::TSTORE))
+    );
+    // CREATE R0, R0, R0, R0, R0 / HALT
+    assert_eq!(
+        run_static(vec![0x91, 0x00, 0x00, 0x00, 0x00]),
+        Some(VmError::StaticViolation(Opcode::CREATE))
+    );
+}
+
+#[test]
+fn test_subroutine_call_and_return() {
+    // LOADI R0, 13 / JSUB R0 / HALT
+    // 13: LOADI R1, 42 / LOG R1 / JRET
+    let mut bytecode = loadi(0, 13);
+    bytecode.extend_from_slice(&[0x07, 0x00, 0x00]);
+    bytecode.extend(loadi(1, 42));
+    bytecode.extend_from_slice(&[0xF0, 0x10, 0x08]);
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    let result = vm.run().unwrap();
+    assert_eq!(result.status, ExecutionStatus::Halted);
+    assert_eq!(result.logs, vec![42]);
+}
+
+#[test]
+fn test_subroutine_immediate_target_keeps_registers() {
+    // LOADI R15, 7 / JSUBI 22 / LOG R15 / HALT
+    // 22: JRET
+    let mut bytecode = loadi(15, 7);
+    bytecode.push(0x09);
+    bytecode.extend_from_slice(&22u64.to_le_bytes());
+    bytecode.extend_from_slice(&[0xF0, 0xF0, 0x00, 0x08]);
+
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    let result = vm.run().unwrap();
+    assert_eq!(result.status, ExecutionStatus::Halted);
+    assert_eq!(result.logs, vec![7]);
+}
+
+#[test]
+fn test_return_stack_bounds() {
+    let new_vm = |bytecode: Vec<u8>| {
+        Vm::new(
+            bytecode,
+            1_000_000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            GasSchedule::default(),
+        )
+    };
+
+    // JRET with nothing to return to
+    assert_eq!(
+        new_vm(vec![0x08]).run().err(),
+        Some(VmError::StackUnderflow)
+    );
+
+    // LOADI R0, 10 / JSUB R0: recurses forever
+    let mut vm = new_vm([loadi(0, 10), vec![0x07, 0x00]].concat());
+    vm.set_max_return_depth(4);
+    assert_eq!(vm.run().err(), Some(VmError::StackOverflow { max: 4 }));
+
+    // LOADI R0, 11 / JSUB R0: lands inside the JSUB operand
+    let mut vm = new_vm([loadi(0, 11), vec![0x07, 0x00]].concat());
+    assert_eq!(vm.run().err(), Some(VmError::InvalidJump(11)));
+}
+
+#[test]
+fn test_block_and_tx_context() {
+    // ORIGIN R0 / COINBASE R1 / BLOCKHASH R2, R4 / BLOCKHASH R3, R5
+    // CHAINID R6 / LOG R6 / GASPRICE R6 / LOG R6 / RETURN R0, R7
+    let bytecode = [
+        loadi(0, 0),
+        loadi(1, 20),
+        loadi(2, 40),
+        loadi(3, 72),
+        loadi(4, 9),
+        loadi(5, 6),
+        loadi(7, 104),
+        vec![0x8C, 0x00, 0x8E, 0x10, 0x8F, 0x24, 0x8F, 0x35],
+        vec![0x8B, 0x60, 0xF0, 0x60, 0x8D, 0x60, 0xF0, 0x60, 0x06, 0x07],
+    ]
+    .concat();
+
+    let hashes = vec![hash(b"7"), hash(b"8"), hash(b"9")];
+    let block = BlockContext {
+        number: 10,
+        timestamp: 1_000,
+        author: Address([0xAA; 20]),
+        chain_id: 77,
+        recent_hashes: hashes.clone().into(),
+    };
+    let tx = TxContext {
+        origin: Address([0x0E; 20]),
+        gas_price: 3,
+    };
+    let mut vm = Vm::new_with_context(
+        bytecode,
+        1_000_000,
+        Address([0xCA; 20]),
+        Address::ZERO,
+        0,
+        block,
+        tx,
+        GasSchedule::default(),
+    );
+    let result = vm.run().unwrap();
+
+    assert_eq!(result.status, ExecutionStatus::Returned);
+    assert_eq!(result.logs, vec![77, 3]);
+    let data = result.return_data;
+    assert_eq!(&data[..20], &[0x0E; 20]);
+    assert_eq!(&data[20..40], &[0xAA; 20]);
+    assert_eq!(&data[40..72], hashes[2].as_bytes());
+    // Block 6 is older than the known hashes and reads as zero.
+    assert_eq!(&data[72..104], &[0u8; 32]);
+}
+
+#[test]
+fn test_transient_storage() {
+    // LOADI R0, 3 / LOADI R1, 9 / TSTORE R0, R1 / TLOAD R2, R0 / LOG R2
+    // SLOAD R3, R0 / LOG R3 / HALT
+    let bytecode = [
+        loadi(0, 3),
+        loadi(1, 9),
+        vec![
+            0x55, 0x01, 0x54, 0x20, 0xF0, 0x20, 0x50, 0x30, 0xF0, 0x30, 0x00,
+        ],
+    ]
+    .concat();
+    let mut vm = Vm::new(
+        bytecode,
+        1_000_000,
+        Address::ZERO,
+        Address::ZERO,
+        0,
+        GasSchedule::default(),
+    );
+    vm.set_storage(Box::new(MapStorage::default()));
+    let result = vm.run().unwrap();
+
+    // Transient slots are separate from persistent ones and cost far less to write.
+    assert_eq!(result.logs, vec![9, 0]);
+    assert!(vm.storage_writes().is_empty());
+    assert!(result.gas_used < GasSchedule::default().sstore_set);
+}
+
+/// Syscall 7 doubles the u64 it is given and echoes the caller's depth.
+struct Doubler;
+
+impl HostFunctions for Doubler {
+    fn gas(&self, id: u64, input: &[u8]) -> Option<u64> {
+        (id == 7).then_some(10 + input.len() as u64)
+    }
+
+    fn call(&self, vm: &Vm<'_>, _id: u64, input: &[u8]) -> Result<SyscallOutput, String> {
+        let value = u64::from_le_bytes(input.try_into().map_err(|_| "expected 8 bytes")?);
+        Ok(SyscallOutput {
+            value: vm.depth() as u64,
+            data: (value * 2).to_le_bytes().to_vec(),
+        })
+    }
+}
+
+#[test]
+fn test_syscall() {
+    let run = |id: u64, len: u64| {
+        // STORE64 R0, R1 / SYSCALL R5, R2, R0, R3, R4 / LOAD64 R6, R4 / LOG R6 / LOG R5 / HALT
+        let bytecode = [
+            loadi(0, 0),
+            loadi(1, 21),
+            loadi(2, id),
+            loadi(3, len),
+            loadi(4, 32),
+            vec![0x43, 0x01, 0x92, 0x52, 0x03, 0x40],
+            vec![0x41, 0x64, 0xF0, 0x60, 0xF0, 0x50, 0x00],
+        ]
+        .concat();
+        let mut vm = Vm::new(
+            bytecode,
+            1_000_000,
+            Address::ZERO,
+            Address::ZERO,
+            0,
+            GasSchedule::default(),
+        );
+        vm.set_host_functions(Rc::new(Doubler));
+        vm.run()
+    };
+
+    let result = run(7, 8).unwrap();
+    assert_eq!(result.logs, vec![42, 0]);
+
+    assert_eq!(run(8, 8).err(), Some(VmError::UnknownSyscall(8)));
+    assert_eq!(
+        run(7, 4).err(),
+        Some(VmError::SyscallFailed {
+            id: 7,
+            reason: "expected 8 bytes".to_string()
+        })
+    );
+}
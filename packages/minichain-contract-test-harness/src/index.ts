@@ -1,3 +1,4 @@
+import { mkdirSync } from "fs";
 import { mkdir } from "fs/promises";
 import { tmpdir } from "os";
 
@@ -30,6 +31,28 @@ export interface DeployContractArgs {
  */
 export const SKIP_BLOCK_PRODUCTION = false;
 
+/**
+ * Directory to write gas profiles of contract queries to, taken from
+ * `MINICHAIN_GAS_PROFILE`. Profiling is off when it is unset.
+ */
+export const GAS_PROFILE_DIR = process.env.MINICHAIN_GAS_PROFILE;
+
+let gasProfileCount = 0;
+
+/**
+ * Extra `minichain call --query` arguments that print a gas profile broken down
+ * by the labels of `sourcePath` and write folded stacks under GAS_PROFILE_DIR.
+ */
+export function gasProfileArgs(sourcePath: string, name: string): string[] {
+  if (!GAS_PROFILE_DIR) {
+    return [];
+  }
+  mkdirSync(GAS_PROFILE_DIR, { recursive: true });
+  gasProfileCount += 1;
+  const folded = `${GAS_PROFILE_DIR}/${gasProfileCount.toString().padStart(4, "0")}-${name}.folded`;
+  return ["--profile", "--source", sourcePath, "--flamegraph", folded];
+}
+
 export function repoPath(...segments: string[]): string {
   return [REPO_ROOT, ...segments].join("/");
 }